@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: MIT
+pragma solidity >=0.8.0;
+// ----------------------------------------------------------------------------
+// Minimal ERC20 interface used by the disperse contract
+// ----------------------------------------------------------------------------
+interface IERC20 {
+    function transferFrom(address from, address to, uint tokens) external returns (bool success);
+}
+// ----------------------------------------------------------------------------
+// Sends several ERC20 transfers from the caller in one atomic transaction.
+// The caller must approve this contract to spend each of the tokens.
+// ----------------------------------------------------------------------------
+contract Disperse {
+    function disperseTokens(address[] calldata tokens, address[] calldata recipients, uint[] calldata values) external {
+        require(tokens.length == recipients.length && tokens.length == values.length, "Length mismatch");
+        for (uint i = 0; i < tokens.length; i++) {
+            require(IERC20(tokens[i]).transferFrom(msg.sender, recipients[i], values[i]), "Transfer failed");
+        }
+    }
+}
@@ -15,6 +15,11 @@ pub fn increment() -> Guard {
     Guard {}
 }
 
+/// Returns counter of concurrent requests.
+pub fn count() -> isize {
+    COUNTER.load(Ordering::Relaxed)
+}
+
 /// Decrements counter of concurrent requests.
 fn decrement() {
     COUNTER.fetch_sub(1, Ordering::Relaxed);
@@ -30,6 +35,6 @@ use std::fmt;
 
 impl fmt::Display for Guard {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", COUNTER.load(Ordering::Relaxed))
+        write!(f, "{}", count())
     }
 }
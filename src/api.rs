@@ -0,0 +1,265 @@
+//! Faucet API description module.
+//!
+//! Endpoints are described once; the description registers routes of the server,
+//! generates the OpenAPI document of the versioned API and renders the manual.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use actix_web::dev::Payload;
+use actix_web::web::Json;
+use actix_web::{FromRequest, HttpRequest, Route};
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Map, Value};
+
+/// Path prefix of the versioned API.
+pub const V1: &str = "/v1/";
+
+/// HTTP methods of endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+}
+
+impl Method {
+    fn as_str(self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Post => "POST",
+        }
+    }
+}
+
+/// Represents a body of a request or a response.
+pub enum Body {
+    /// Plain text.
+    Text,
+    /// JSON described by the schema of a Rust type.
+    Json {
+        name: fn() -> String,
+        schema: fn(&mut SchemaGenerator) -> Schema,
+    },
+}
+
+/// Returns the description of a JSON body of type `T`.
+pub fn json<T: JsonSchema>() -> Body {
+    Body::Json {
+        name: T::schema_name,
+        schema: schema::<T>,
+    }
+}
+
+fn schema<T: JsonSchema>(gen: &mut SchemaGenerator) -> Schema {
+    gen.subschema_for::<T>()
+}
+
+/// Status code and description of a response with a plain text body.
+pub type Status = (u16, &'static str);
+
+pub const PARTIAL_SUCCESS: Status = (207, "Some transfers failed");
+pub const BAD_REQUEST: Status = (400, "Invalid request");
+pub const UNAUTHORIZED: Status = (401, "Invalid token");
+pub const NOT_FOUND: Status = (404, "Disabled");
+pub const CAP_EXCEEDED: Status = (429, "Cap exceeded");
+pub const INTERNAL_ERROR: Status = (500, "Internal error");
+
+/// Represents an endpoint of the server.
+pub struct Endpoint {
+    pub method: Method,
+    pub path: &'static str,
+    pub summary: &'static str,
+    pub request: Option<Body>,
+    /// Body of a successful response; `None` means an empty body.
+    pub response: Option<Body>,
+    /// Other responses the endpoint may return.
+    pub errors: &'static [Status],
+    /// Creates the route serving the endpoint.
+    pub route: fn() -> Route,
+}
+
+impl Endpoint {
+    /// Checks if the endpoint belongs to the versioned API.
+    pub fn is_versioned(&self) -> bool {
+        self.path.starts_with(V1)
+    }
+}
+
+/// Generates the OpenAPI 3 document of the versioned endpoints.
+pub fn openapi(endpoints: &[Endpoint]) -> Value {
+    let mut gen = SchemaSettings::openapi3().into_generator();
+    let mut paths = Map::new();
+    for e in endpoints.iter().filter(|e| e.is_versioned()) {
+        let mut responses = Map::new();
+        let mut ok = json!({ "description": "Success" });
+        if let Some(body) = &e.response {
+            ok["content"] = content(body, &mut gen);
+        }
+        responses.insert("200".into(), ok);
+        for (status, description) in e.errors {
+            responses.insert(status.to_string(), text(description));
+        }
+
+        let mut operation = json!({ "summary": e.summary, "responses": responses });
+        if let Some(body) = &e.request {
+            operation["requestBody"] = json!({
+                "required": true,
+                "content": content(body, &mut gen),
+            });
+        }
+        let path = paths.entry(e.path).or_insert_with(|| json!({}));
+        path[e.method.as_str().to_lowercase()] = operation;
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Neon Faucet",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": paths,
+        "components": { "schemas": gen.take_definitions() },
+    })
+}
+
+/// Returns the content object of the `body`.
+fn content(body: &Body, gen: &mut SchemaGenerator) -> Value {
+    match body {
+        Body::Text => json!({ "text/plain": { "schema": { "type": "string" } } }),
+        Body::Json { schema, .. } => json!({ "application/json": { "schema": schema(gen) } }),
+    }
+}
+
+/// Returns the response object with a plain text body.
+fn text(description: &str) -> Value {
+    json!({
+        "description": description,
+        "content": { "text/plain": { "schema": { "type": "string" } } },
+    })
+}
+
+/// Renders Markdown tables of the unversioned and versioned endpoints.
+pub fn manual(endpoints: &[Endpoint]) -> String {
+    let workload = |body: &Option<Body>| match body {
+        None => String::new(),
+        Some(Body::Text) => "text".into(),
+        Some(Body::Json { .. }) => "JSON".into(),
+    };
+    let schema = |body: &Option<Body>| match body {
+        None => String::new(),
+        Some(Body::Text) => "text".into(),
+        Some(Body::Json { name, .. }) => format!("`{}`", name()),
+    };
+
+    let mut md = String::from(
+        "|:-:|:-:|-\n|**Endpoint**|**Method**|**Workload**|**Description**|\n|:-|:-:|:-:|-\n",
+    );
+    for e in endpoints.iter().filter(|e| !e.is_versioned()) {
+        md += &format!(
+            "| {} | {} | {} | {}\n",
+            e.path.trim_start_matches('/'),
+            e.method.as_str(),
+            workload(&e.request),
+            e.summary
+        );
+    }
+    md += "|-\n\nThe versioned API validates requests and is described by the OpenAPI \
+           document served at `/v1/openapi.json`:\n\n";
+    md += "|:-:|:-:|-\n|**Endpoint**|**Method**|**Request**|**Response**|**Description**|\n";
+    md += "|:-|:-:|:-:|:-:|-\n";
+    for e in endpoints.iter().filter(|e| e.is_versioned()) {
+        md += &format!(
+            "| {} | {} | {} | {} | {}\n",
+            e.path,
+            e.method.as_str(),
+            schema(&e.request),
+            schema(&e.response),
+            e.summary
+        );
+    }
+    md += "|-\n";
+    md
+}
+
+/// Checks a request beyond its JSON structure.
+pub trait Validate {
+    fn validate(&self) -> Result<(), String>;
+}
+
+/// Extracts a JSON request body and validates it; responds with 400 if it's invalid.
+pub struct Valid<T>(pub T);
+
+impl<T> Valid<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: DeserializeOwned + Validate + 'static> FromRequest for Valid<T> {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let json = Json::<T>::from_request(req, payload);
+        Box::pin(async move {
+            let value = json.await?.into_inner();
+            value
+                .validate()
+                .map_err(actix_web::error::ErrorBadRequest)?;
+            Ok(Valid(value))
+        })
+    }
+}
+
+#[test]
+fn test_openapi() {
+    use actix_web::web::get;
+
+    #[derive(serde::Deserialize, JsonSchema)]
+    #[allow(unused)]
+    struct Request {
+        wallet: String,
+    }
+
+    let endpoints = [
+        Endpoint {
+            method: Method::Get,
+            path: "/request_version",
+            summary: "Requests version",
+            request: None,
+            response: Some(Body::Text),
+            errors: &[],
+            route: || get().to(|| async { "" }),
+        },
+        Endpoint {
+            method: Method::Post,
+            path: "/v1/request",
+            summary: "Requests tokens",
+            request: Some(json::<Request>()),
+            response: None,
+            errors: &[BAD_REQUEST, CAP_EXCEEDED],
+            route: || get().to(|| async { "" }),
+        },
+    ];
+
+    let doc = openapi(&endpoints);
+    assert!(doc["paths"].get("/request_version").is_none());
+    let post = &doc["paths"]["/v1/request"]["post"];
+    assert_eq!(
+        post["requestBody"]["content"]["application/json"]["schema"]["$ref"],
+        "#/components/schemas/Request"
+    );
+    assert!(post["responses"].get("429").is_some());
+    assert!(post["responses"].get("500").is_none());
+    assert_eq!(
+        doc["components"]["schemas"]["Request"]["properties"]["wallet"]["type"],
+        "string"
+    );
+
+    let md = manual(&endpoints);
+    assert!(md.contains("| request_version | GET |  | Requests version"));
+    assert!(md.contains("| /v1/request | POST | `Request` |  | Requests tokens"));
+}
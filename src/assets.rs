@@ -0,0 +1,688 @@
+//! Faucet multi-asset requests module.
+//!
+//! Serves several assets to one wallet in a single request:
+//! every cap is checked before anything is sent.
+
+use std::collections::HashMap;
+use std::str::FromStr as _;
+
+use eyre::{eyre, Result};
+use tokio::sync::RwLock;
+use tracing::{error, info};
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::erc20_tokens::{self, Amount, TokenResult};
+use crate::events::{self, Event};
+use crate::neon_token::{self, AirdropLimiter, AirdropLimiterError, Requester};
+use crate::{api, config, ethereum, id::ReqId, nft_tokens, sol_token, spl_tokens};
+
+/// Airdrop limiters of all assets.
+pub struct Limiters {
+    /// Limiters of NEON by names of Neon EVM networks.
+    pub neon: HashMap<String, RwLock<AirdropLimiter>>,
+    /// Limiters of SPL tokens by mints.
+    pub spl: TokenLimiters,
+    /// Limiter of SOL with caps in lamports.
+    pub sol: RwLock<AirdropLimiter>,
+    /// Limiters of NFT collections by addresses.
+    pub nft: TokenLimiters,
+}
+
+/// Represents limiters of tokens by keys, created on first request of a token.
+pub struct TokenLimiters {
+    /// Limiter holding trusted proxies and the blacklist for new limiters.
+    template: AirdropLimiter,
+    limiters: RwLock<HashMap<String, AirdropLimiter>>,
+}
+
+impl TokenLimiters {
+    pub fn new(template: AirdropLimiter) -> Self {
+        Self {
+            template,
+            limiters: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Checks and accounts amounts as `(token, amount, max_amount, per_time_max_amount)`.
+    /// Either all amounts are accounted in caps of their tokens or none of them.
+    pub async fn check_amounts(
+        &self,
+        req: &impl Requester,
+        amounts: &[(&str, u64, u64, u64)],
+    ) -> Result<(), AirdropLimiterError> {
+        let mut limiters = self.limiters.write().await;
+        for (accounted, &(token, amount, max_amount, per_time_max_amount)) in
+            amounts.iter().enumerate()
+        {
+            let limiter = limiters
+                .entry(token.to_string())
+                .or_insert_with(|| self.template.with_caps(max_amount, per_time_max_amount));
+            if let Err(err) = limiter.check_amount(req, amount) {
+                for &(token, amount, _, _) in &amounts[..accounted] {
+                    if let Some(limiter) = limiters.get_mut(token) {
+                        limiter.release_amount(req, amount);
+                    }
+                }
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    /// Releases amounts as `(token, amount)` accounted for an airdrop which won't be sent.
+    pub async fn release_amounts(&self, req: &impl Requester, amounts: &[(&str, u64)]) {
+        let mut limiters = self.limiters.write().await;
+        for &(token, amount) in amounts {
+            if let Some(limiter) = limiters.get_mut(token) {
+                limiter.release_amount(req, amount);
+            }
+        }
+    }
+
+    /// Clears caches of all tokens at the end of a time slice.
+    pub async fn clear_caches(&self) {
+        for limiter in self.limiters.write().await.values_mut() {
+            limiter.clear_cache();
+        }
+    }
+}
+
+/// Represents a request of several assets to one wallet.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct Request {
+    /// Recipient of the assets: Ethereum address or, for SPL and SOL, Solana wallet (base58).
+    pub wallet: String,
+    pub assets: Vec<Asset>,
+}
+
+/// Represents a requested asset.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct Asset {
+    pub kind: Kind,
+    /// Recipient of the asset if it differs from the wallet of the request.
+    pub wallet: Option<String>,
+    /// Network of NEON, symbol, alias or address of an ERC20 token, mint or symbol
+    /// of an SPL token or address of an NFT collection.
+    /// If missing, NEON of the default network or all ERC20 or SPL tokens.
+    pub token: Option<String>,
+    /// Token id of an ERC1155 NFT collection. If missing, the first configured one.
+    pub token_id: Option<u64>,
+    /// Amount to be received: whole tokens or, for ERC20, a decimal string like "0.25".
+    pub amount: Amount,
+    #[serde(default)]
+    pub unit: Unit,
+    /// Commitment level to wait for NEON; overrides the fire-and-forget mode.
+    pub commitment: Option<String>,
+}
+
+/// Kinds of assets served by the faucet.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, serde::Serialize, schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Neon,
+    Erc20,
+    Spl,
+    Sol,
+    Nft,
+}
+
+/// Units of requested amounts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Unit {
+    /// Whole tokens.
+    Whole,
+    /// Fractions of a token: galans of NEON, base units of ERC20 and SPL or lamports of SOL.
+    /// NFT are counted in tokens.
+    Fractions,
+}
+
+impl Default for Unit {
+    fn default() -> Self {
+        Unit::Whole
+    }
+}
+
+impl api::Validate for Request {
+    fn validate(&self) -> Result<(), String> {
+        if self.assets.is_empty() {
+            return Err("No assets are requested".into());
+        }
+        for asset in &self.assets {
+            let wallet = asset.wallet.as_ref().unwrap_or(&self.wallet);
+            match asset.kind {
+                Kind::Spl | Kind::Sol => Pubkey::from_str(wallet)
+                    .map(|_| ())
+                    .map_err(|e| e.to_string()),
+                _ => ethereum::address_from_str(wallet)
+                    .map(|_| ())
+                    .map_err(|e| e.to_string()),
+            }
+            .map_err(|e| format!("Invalid wallet '{}': {}", wallet, e))?;
+            api::Validate::validate(&asset.amount)?;
+        }
+        Ok(())
+    }
+}
+
+/// Token name of SOL in results.
+const SOL: &str = "SOL";
+
+/// Represents an error of a request which can't be served.
+#[derive(thiserror::Error, Debug)]
+#[error("{0}")]
+pub struct InvalidRequest(pub String);
+
+/// Represents an airdrop of a single asset.
+#[derive(Debug)]
+pub enum Airdrop {
+    Neon(neon_token::Airdrop),
+    Erc20(erc20_tokens::Airdrop),
+    Spl(spl_tokens::Airdrop),
+    Sol(sol_token::Airdrop),
+    Nft(nft_tokens::Airdrop),
+}
+
+/// Represents an airdrop checked against the caps.
+pub enum Checked {
+    Neon(neon_token::Airdrop),
+    Erc20(erc20_tokens::Prepared),
+    Spl(Pubkey, Vec<spl_tokens::Transfer>),
+    /// Recipient and amount in lamports.
+    Sol(Pubkey, u64),
+    Nft(nft_tokens::Prepared),
+}
+
+/// Represents the result of the airdrop of an asset (of each token if ERC20 token is missing).
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct AssetResult {
+    pub kind: Kind,
+    #[serde(flatten)]
+    pub result: TokenResult,
+}
+
+impl Request {
+    /// Converts the requested assets into airdrops.
+    pub fn into_airdrops(self) -> Result<Vec<Airdrop>> {
+        if self.assets.is_empty() {
+            return Err(InvalidRequest("No assets are requested".into()).into());
+        }
+        let wallet = self.wallet;
+        self.assets
+            .into_iter()
+            .map(|asset| {
+                let wallet = asset.wallet.clone().unwrap_or_else(|| wallet.clone());
+                asset.into_airdrop(wallet)
+            })
+            .collect()
+    }
+}
+
+impl Asset {
+    fn into_airdrop(self, wallet: String) -> Result<Airdrop> {
+        let in_fractions = self.unit == Unit::Fractions;
+        match self.kind {
+            Kind::Neon => Ok(Airdrop::Neon(neon_token::Airdrop {
+                wallet,
+                amount: integer_amount("NEON", self.amount)?,
+                in_fractions,
+                network: self.token.unwrap_or_default(),
+                commitment: self.commitment,
+            })),
+            Kind::Erc20 => Ok(Airdrop::Erc20(erc20_tokens::Airdrop {
+                wallet,
+                token_addr: None,
+                token: self.token,
+                amount: self.amount,
+                in_base_units: in_fractions,
+            })),
+            Kind::Spl => Ok(Airdrop::Spl(spl_tokens::Airdrop {
+                wallet,
+                token: self.token,
+                amount: integer_amount("SPL", self.amount)?,
+                in_fractions,
+            })),
+            Kind::Sol => Ok(Airdrop::Sol(sol_token::Airdrop {
+                wallet,
+                amount: integer_amount("SOL", self.amount)?,
+                in_lamports: in_fractions,
+            })),
+            Kind::Nft => Ok(Airdrop::Nft(nft_tokens::Airdrop {
+                wallet,
+                contract: self
+                    .token
+                    .ok_or_else(|| InvalidRequest("NFT collection is missing".into()))?,
+                token_id: self.token_id,
+                amount: Some(integer_amount("NFT", self.amount)?),
+            })),
+        }
+    }
+}
+
+/// Converts the amount of an asset other than ERC20 into an integer.
+fn integer_amount(asset: &str, amount: Amount) -> Result<u64> {
+    match amount {
+        Amount::Number(amount) => Ok(amount),
+        Amount::Text(text) => text
+            .parse()
+            .map_err(|_| InvalidRequest(format!("Invalid {} amount '{}'", asset, text)).into()),
+    }
+}
+
+/// Checks every airdrop against the caps, then sends all of them.
+/// Fails without sending anything if an airdrop is invalid or exceeds a cap.
+/// Returns the result per asset; failed transfers don't stop the others.
+pub async fn process(
+    id: &ReqId,
+    req: &impl Requester,
+    limiters: &Limiters,
+    airdrops: Vec<Airdrop>,
+) -> Result<Vec<AssetResult>> {
+    let checked = check_all(id, req, limiters, airdrops).await?;
+    let mut results = Vec::with_capacity(checked.len());
+    for airdrop in checked {
+        results.extend(send(id, req, limiters, airdrop).await);
+    }
+    Ok(results)
+}
+
+/// Checks every airdrop against the caps.
+/// Releases the caps of all airdrops if one is invalid or exceeds a cap.
+pub async fn check_all(
+    id: &ReqId,
+    req: &impl Requester,
+    limiters: &Limiters,
+    airdrops: Vec<Airdrop>,
+) -> Result<Vec<Checked>> {
+    let mut checked = Vec::with_capacity(airdrops.len());
+    for airdrop in airdrops {
+        match check(id, req, limiters, airdrop).await {
+            Ok(airdrop) => checked.push(airdrop),
+            Err(err) => {
+                for airdrop in checked {
+                    cancel(req, limiters, airdrop).await;
+                }
+                return Err(err);
+            }
+        }
+    }
+    events::publish(id, Event::RateCheckPassed);
+    Ok(checked)
+}
+
+/// Sends a checked airdrop, returns the results (per token if ERC20 token is missing).
+/// Caps of a failed airdrop are released.
+pub async fn send(
+    id: &ReqId,
+    req: &impl Requester,
+    limiters: &Limiters,
+    airdrop: Checked,
+) -> Vec<AssetResult> {
+    match airdrop {
+        Checked::Neon(airdrop) => {
+            let result = match neon_token::airdrop(id, &airdrop).await {
+                Ok(tx) => TokenResult {
+                    token: airdrop.network.clone(),
+                    tx: Some(tx),
+                    error: None,
+                },
+                Err(err) => {
+                    error!(
+                        "{} Failed airdrop of NEON of '{}': {}",
+                        id, airdrop.network, err
+                    );
+                    let result = TokenResult {
+                        token: airdrop.network.clone(),
+                        tx: None,
+                        error: Some(err.to_string()),
+                    };
+                    cancel(req, limiters, Checked::Neon(airdrop)).await;
+                    result
+                }
+            };
+            vec![AssetResult {
+                kind: Kind::Neon,
+                result,
+            }]
+        }
+        Checked::Erc20(prepared) => {
+            let sent = erc20_tokens::send(id, prepared).await;
+            sent.into_iter()
+                .map(|result| AssetResult {
+                    kind: Kind::Erc20,
+                    result,
+                })
+                .collect()
+        }
+        Checked::Spl(wallet, transfers) => {
+            // All tokens are sent in one transaction
+            let (tx, error) = match spl_tokens::airdrop(id, wallet, &transfers).await {
+                Ok(signature) => (Some(signature.to_string()), None),
+                Err(err) => {
+                    error!("{} Failed airdrop of SPL tokens: {}", id, err);
+                    (None, Some(err.to_string()))
+                }
+            };
+            let results = transfers
+                .iter()
+                .map(|t| AssetResult {
+                    kind: Kind::Spl,
+                    result: TokenResult {
+                        token: t.token.clone(),
+                        tx: tx.clone(),
+                        error: error.clone(),
+                    },
+                })
+                .collect();
+            if error.is_some() {
+                cancel(req, limiters, Checked::Spl(wallet, transfers)).await;
+            }
+            results
+        }
+        Checked::Sol(wallet, lamports) => {
+            let result = match sol_token::airdrop(id, wallet, lamports).await {
+                Ok(signature) => TokenResult {
+                    token: SOL.into(),
+                    tx: Some(signature.to_string()),
+                    error: None,
+                },
+                Err(err) => {
+                    error!("{} Failed airdrop of SOL: {}", id, err);
+                    cancel(req, limiters, Checked::Sol(wallet, lamports)).await;
+                    TokenResult {
+                        token: SOL.into(),
+                        tx: None,
+                        error: Some(err.to_string()),
+                    }
+                }
+            };
+            vec![AssetResult {
+                kind: Kind::Sol,
+                result,
+            }]
+        }
+        Checked::Nft(prepared) => {
+            let (collection, amount) = (prepared.collection.clone(), prepared.amount);
+            // The per wallet cap of a failed airdrop is released by the NFT module
+            let sent = nft_tokens::send(id, prepared).await;
+            if let Err(err) = &sent {
+                error!("{} Failed airdrop of NFT {}: {}", id, collection, err);
+                limiters
+                    .nft
+                    .release_amounts(req, &[(&collection, amount)])
+                    .await;
+            }
+            let result = TokenResult::new(collection, &sent);
+            vec![AssetResult {
+                kind: Kind::Nft,
+                result,
+            }]
+        }
+    }
+}
+
+/// Classifies results of the airdrops.
+pub fn outcome(results: &[AssetResult]) -> erc20_tokens::Outcome {
+    erc20_tokens::outcome(results.iter().map(|r| &r.result))
+}
+
+/// Validates the airdrop and accounts it in the caps.
+async fn check(
+    id: &ReqId,
+    req: &impl Requester,
+    limiters: &Limiters,
+    airdrop: Airdrop,
+) -> Result<Checked> {
+    match airdrop {
+        Airdrop::Neon(mut airdrop) => {
+            if airdrop.network.is_empty() {
+                airdrop.network = config::DEFAULT_NETWORK.into();
+            }
+            if let Some(ref commitment) = airdrop.commitment {
+                if config::parse_commitment(commitment).is_none() {
+                    let err = format!("Invalid commitment '{}'", commitment);
+                    return Err(InvalidRequest(err).into());
+                }
+            }
+            if !config::solana_network_exists(&airdrop.network) {
+                let err = format!("Unknown network '{}'", airdrop.network);
+                return Err(InvalidRequest(err).into());
+            }
+            if config::solana_evm_backend() && airdrop.network != config::DEFAULT_NETWORK {
+                let err = format!(
+                    "Network '{}' is not served by the evm backend",
+                    airdrop.network
+                );
+                return Err(InvalidRequest(err).into());
+            }
+            let limiter = limiters
+                .neon
+                .get(&airdrop.network)
+                .ok_or_else(|| eyre!("No limiter of network '{}'", airdrop.network))?;
+            limiter.write().await.check_cache(req, &airdrop)?;
+            info!("{} Checked NEON {:?}", id, airdrop);
+            Ok(Checked::Neon(airdrop))
+        }
+        Airdrop::Erc20(airdrop) => Ok(Checked::Erc20(erc20_tokens::prepare(id, &airdrop).await?)),
+        Airdrop::Spl(airdrop) => {
+            if !config::solana_enabled() {
+                return Err(InvalidRequest("SPL airdrops are disabled".into()).into());
+            }
+            let (wallet, transfers) = spl_tokens::prepare(id, &airdrop).await?;
+            let amounts = transfers
+                .iter()
+                .map(|t| {
+                    (
+                        t.token.as_str(),
+                        t.amount,
+                        t.max_amount,
+                        t.per_time_max_amount,
+                    )
+                })
+                .collect::<Vec<_>>();
+            limiters.spl.check_amounts(req, &amounts).await?;
+            info!("{} Checked SPL {:?}", id, airdrop);
+            Ok(Checked::Spl(wallet, transfers))
+        }
+        Airdrop::Sol(airdrop) => {
+            if !config::solana_enabled() || config::solana_sol_max_amount() == 0 {
+                return Err(InvalidRequest("SOL airdrops are disabled".into()).into());
+            }
+            let (wallet, lamports) = sol_token::prepare(id, &airdrop)?;
+            limiters.sol.write().await.check_amount(req, lamports)?;
+            info!("{} Checked SOL {:?}", id, airdrop);
+            Ok(Checked::Sol(wallet, lamports))
+        }
+        Airdrop::Nft(airdrop) => {
+            let prepared = nft_tokens::prepare(id, &airdrop).await?;
+            let amounts = [(
+                prepared.collection.as_str(),
+                prepared.amount,
+                prepared.max_amount,
+                prepared.per_time_max_amount,
+            )];
+            if let Err(err) = limiters.nft.check_amounts(req, &amounts).await {
+                nft_tokens::cancel(prepared).await;
+                return Err(err.into());
+            }
+            info!("{} Checked NFT {:?}", id, airdrop);
+            Ok(Checked::Nft(prepared))
+        }
+    }
+}
+
+/// Releases the caps accounted for an airdrop which won't be sent.
+async fn cancel(req: &impl Requester, limiters: &Limiters, airdrop: Checked) {
+    match airdrop {
+        Checked::Neon(airdrop) => {
+            if let Some(limiter) = limiters.neon.get(&airdrop.network) {
+                limiter.write().await.release_cache(req, &airdrop);
+            }
+        }
+        Checked::Erc20(prepared) => erc20_tokens::cancel(prepared).await,
+        Checked::Spl(_, transfers) => {
+            let amounts = transfers
+                .iter()
+                .map(|t| (t.token.as_str(), t.amount))
+                .collect::<Vec<_>>();
+            limiters.spl.release_amounts(req, &amounts).await;
+        }
+        Checked::Sol(_, lamports) => limiters.sol.write().await.release_amount(req, lamports),
+        Checked::Nft(prepared) => {
+            limiters
+                .nft
+                .release_amounts(req, &[(&prepared.collection, prepared.amount)])
+                .await;
+            nft_tokens::cancel(prepared).await;
+        }
+    }
+}
+
+#[test]
+fn test_into_airdrops() {
+    let request: Request = serde_json::from_str(
+        r#"{ "wallet": "0x4570e07200b6332989Dc04fA2a671b839D26eF0E", "assets": [
+            { "kind": "neon", "amount": 1 },
+            { "kind": "neon", "token": "second", "amount": "5", "unit": "fractions" },
+            { "kind": "erc20", "token": "USDT", "amount": "0.25" }
+        ] }"#,
+    )
+    .unwrap();
+    let airdrops = request.into_airdrops().unwrap();
+    assert_eq!(airdrops.len(), 3);
+    assert!(matches!(&airdrops[0], Airdrop::Neon(a) if a.amount == 1 && !a.in_fractions));
+    match &airdrops[1] {
+        Airdrop::Neon(a) => assert!(a.amount == 5 && a.in_fractions && a.network == "second"),
+        _ => panic!("NEON airdrop expected"),
+    }
+    assert!(matches!(&airdrops[2], Airdrop::Erc20(a) if a.token.as_deref() == Some("USDT")));
+
+    let request: Request = serde_json::from_str(
+        r#"{ "wallet": "0x4570e07200b6332989Dc04fA2a671b839D26eF0E", "assets": [
+            { "kind": "spl", "wallet": "11111111111111111111111111111111", "amount": 2 },
+            { "kind": "sol", "wallet": "11111111111111111111111111111111", "amount": "7",
+              "unit": "fractions" },
+            { "kind": "nft", "token": "0x4570e07200b6332989Dc04fA2a671b839D26eF0E", "amount": 3,
+              "token_id": 1 }
+        ] }"#,
+    )
+    .unwrap();
+    assert!(api::Validate::validate(&request).is_ok());
+    let airdrops = request.into_airdrops().unwrap();
+    assert_eq!(airdrops.len(), 3);
+    match &airdrops[0] {
+        Airdrop::Spl(a) => {
+            assert_eq!(a.wallet, "11111111111111111111111111111111");
+            assert!(a.token.is_none() && a.amount == 2 && !a.in_fractions);
+        }
+        _ => panic!("SPL airdrop expected"),
+    }
+    assert!(matches!(&airdrops[1], Airdrop::Sol(a) if a.amount == 7 && a.in_lamports));
+    match &airdrops[2] {
+        Airdrop::Nft(a) => {
+            assert_eq!(a.wallet, "0x4570e07200b6332989Dc04fA2a671b839D26eF0E");
+            assert_eq!(a.contract, "0x4570e07200b6332989Dc04fA2a671b839D26eF0E");
+            assert_eq!((a.token_id, a.amount), (Some(1), Some(3)));
+        }
+        _ => panic!("NFT airdrop expected"),
+    }
+
+    let request: Request = serde_json::from_str(
+        r#"{ "wallet": "0x4570e07200b6332989Dc04fA2a671b839D26eF0E", "assets": [
+            { "kind": "nft", "amount": 1 }
+        ] }"#,
+    )
+    .unwrap();
+    assert!(request.into_airdrops().unwrap_err().is::<InvalidRequest>());
+
+    let request: Request = serde_json::from_str(
+        r#"{ "wallet": "0x4570e07200b6332989Dc04fA2a671b839D26eF0E", "assets": [
+            { "kind": "neon", "amount": "0.5" }
+        ] }"#,
+    )
+    .unwrap();
+    assert!(request.into_airdrops().is_err());
+}
+
+#[test]
+fn test_validate_wallets() {
+    let request = |wallet: &str, kind: &str| -> Request {
+        serde_json::from_str(&format!(
+            r#"{{ "wallet": "{}", "assets": [{{ "kind": "{}", "amount": 1 }}] }}"#,
+            wallet, kind
+        ))
+        .unwrap()
+    };
+    let ethereum = "0x4570e07200b6332989Dc04fA2a671b839D26eF0E";
+    let solana = "11111111111111111111111111111111";
+
+    for kind in ["neon", "erc20", "nft"] {
+        assert!(api::Validate::validate(&request(ethereum, kind)).is_ok());
+        assert!(api::Validate::validate(&request(solana, kind)).is_err());
+    }
+    for kind in ["spl", "sol"] {
+        assert!(api::Validate::validate(&request(solana, kind)).is_ok());
+        assert!(api::Validate::validate(&request(ethereum, kind)).is_err());
+    }
+}
+
+#[test]
+fn test_token_limiters() {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    rt.block_on(async {
+        let peer = std::net::IpAddr::from([127, 0, 0, 1]);
+        let limiters = TokenLimiters::new(AirdropLimiter::new(
+            Default::default(),
+            Default::default(),
+            0,
+            0,
+        ));
+
+        let amounts = [("a", 5, 10, 10), ("b", 5, 10, 10)];
+        assert!(limiters.check_amounts(&peer, &amounts).await.is_ok());
+        // "a" is released when "b" exceeds its cap
+        let amounts = [("a", 5, 10, 10), ("b", 6, 10, 10)];
+        assert!(limiters.check_amounts(&peer, &amounts).await.is_err());
+        assert!(limiters
+            .check_amounts(&peer, &[("a", 5, 10, 10)])
+            .await
+            .is_ok());
+
+        limiters.release_amounts(&peer, &[("a", 10)]).await;
+        assert!(limiters
+            .check_amounts(&peer, &[("a", 10, 10, 10)])
+            .await
+            .is_ok());
+
+        limiters.clear_caches().await;
+        let amounts = [("a", 10, 10, 10), ("b", 10, 10, 10)];
+        assert!(limiters.check_amounts(&peer, &amounts).await.is_ok());
+    });
+}
+
+#[test]
+fn test_cancel_sol() {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    rt.block_on(async {
+        let peer = std::net::IpAddr::from([127, 0, 0, 1]);
+        let limiter = || AirdropLimiter::new(Default::default(), Default::default(), 10, 10);
+        let limiters = Limiters {
+            neon: HashMap::new(),
+            spl: TokenLimiters::new(limiter()),
+            sol: RwLock::new(limiter()),
+            nft: TokenLimiters::new(limiter()),
+        };
+
+        assert!(limiters.sol.write().await.check_amount(&peer, 10).is_ok());
+        cancel(&peer, &limiters, Checked::Sol(Pubkey::new_unique(), 10)).await;
+        assert!(limiters.sol.write().await.check_amount(&peer, 10).is_ok());
+        assert!(limiters.sol.write().await.check_amount(&peer, 1).is_err());
+    });
+}
@@ -27,6 +27,7 @@ lazy_static::lazy_static! {
 
 pub const DEFAULT_CONFIG: &str = "faucet.conf";
 pub const AUTO: &str = "auto";
+pub const DEFAULT_NETWORK: &str = "default";
 
 /// Represents the config errors.
 #[derive(thiserror::Error, Debug)]
@@ -43,6 +44,9 @@ pub enum Error {
     #[error("Failed to parse integer number from config")]
     ParseInt(#[from] std::num::ParseIntError),
 
+    #[error("Failed to parse float number from config")]
+    ParseFloat(#[from] std::num::ParseFloatError),
+
     #[error("Failed to parse string literal '{0}' from config")]
     ParseString(String),
 
@@ -82,14 +86,42 @@ const FAUCET_RPC_BIND: &str = "FAUCET_RPC_BIND";
 const FAUCET_RPC_PORT: &str = "FAUCET_RPC_PORT";
 const FAUCET_RPC_ALLOWED_ORIGINS: &str = "FAUCET_RPC_ALLOWED_ORIGINS";
 const FAUCET_RPC_BLACKLISTED_IPS: &str = "FAUCET_RPC_BLACKLISTED_IPS";
+const FAUCET_RPC_GRPC_PORT: &str = "FAUCET_RPC_GRPC_PORT";
+const FAUCET_RPC_EVENTS_TOKEN: &str = "FAUCET_RPC_EVENTS_TOKEN";
+const FAUCET_RPC_ADMIN_TOKEN: &str = "FAUCET_RPC_ADMIN_TOKEN";
 const FAUCET_WEB3_ENABLE: &str = "FAUCET_WEB3_ENABLE";
 const WEB3_RPC_URL: &str = "WEB3_RPC_URL";
+const WEB3_WS_URL: &str = "WEB3_WS_URL";
+const WEB3_REQUEST_TIMEOUT_SECS: &str = "WEB3_REQUEST_TIMEOUT_SECS";
 const WEB3_PRIVATE_KEY: &str = "WEB3_PRIVATE_KEY";
 const NEON_ERC20_TOKENS: &str = "NEON_ERC20_TOKENS";
+const NEON_ERC20_TOKEN_ALIASES: &str = "NEON_ERC20_TOKEN_ALIASES";
+const NEON_ERC20_TOKEN_MODES: &str = "NEON_ERC20_TOKEN_MODES";
 const NEON_ERC20_MAX_AMOUNT: &str = "NEON_ERC20_MAX_AMOUNT";
+const NEON_ERC20_PER_TIME_MAX_AMOUNT: &str = "NEON_ERC20_PER_TIME_MAX_AMOUNT";
+const NEON_ERC20_TIME_SLICE_SECS: &str = "NEON_ERC20_TIME_SLICE_SECS";
+const NEON_ERC20_TOKENS_REFRESH_SECS: &str = "NEON_ERC20_TOKENS_REFRESH_SECS";
+const WEB3_EIP1559: &str = "WEB3_EIP1559";
+const WEB3_MAX_FEE_PER_GAS: &str = "WEB3_MAX_FEE_PER_GAS";
+const WEB3_MAX_PRIORITY_FEE_PER_GAS: &str = "WEB3_MAX_PRIORITY_FEE_PER_GAS";
+const WEB3_GAS_MULTIPLIER: &str = "WEB3_GAS_MULTIPLIER";
+const WEB3_CONFIRMATIONS: &str = "WEB3_CONFIRMATIONS";
+const WEB3_STUCK_TIMEOUT_SECS: &str = "WEB3_STUCK_TIMEOUT_SECS";
+const WEB3_MAX_REPLACEMENTS: &str = "WEB3_MAX_REPLACEMENTS";
+const WEB3_CANCEL_STUCK: &str = "WEB3_CANCEL_STUCK";
+const WEB3_FEE_BUMP_PERCENT: &str = "WEB3_FEE_BUMP_PERCENT";
+const WEB3_BATCH_CONTRACT: &str = "WEB3_BATCH_CONTRACT";
+const WEB3_BATCH_WINDOW_MS: &str = "WEB3_BATCH_WINDOW_MS";
 const FAUCET_SOLANA_ENABLE: &str = "FAUCET_SOLANA_ENABLE";
 const SOLANA_URL: &str = "SOLANA_URL";
 const SOLANA_COMMITMENT: &str = "SOLANA_COMMITMENT";
+const SOLANA_PREFLIGHT_COMMITMENT: &str = "SOLANA_PREFLIGHT_COMMITMENT";
+const SOLANA_CONFIRM_COMMITMENT: &str = "SOLANA_CONFIRM_COMMITMENT";
+const SOLANA_FIRE_AND_FORGET: &str = "SOLANA_FIRE_AND_FORGET";
+const SOLANA_SIMULATE: &str = "SOLANA_SIMULATE";
+const SOLANA_DURABLE_NONCE: &str = "SOLANA_DURABLE_NONCE";
+const SOLANA_NONCE_ACCOUNTS: &str = "SOLANA_NONCE_ACCOUNTS";
+const SOLANA_NONCE_ACCOUNTS_COUNT: &str = "SOLANA_NONCE_ACCOUNTS_COUNT";
 const EVM_LOADER: &str = "EVM_LOADER";
 const NEON_SEED_VERSION: &str = "NEON_SEED_VERSION";
 const NEON_TOKEN_MINT: &str = "NEON_TOKEN_MINT";
@@ -97,10 +129,14 @@ const NEON_TOKEN_MINT_DECIMALS: &str = "NEON_TOKEN_MINT_DECIMALS";
 const NEON_COMPUTE_UNITS: &str = "NEON_COMPUTE_UNITS";
 const NEON_HEAP_FRAME: &str = "NEON_HEAP_FRAME";
 const NEON_ADDITIONAL_FEE: &str = "NEON_ADDITIONAL_FEE";
+const NEON_BACKEND: &str = "NEON_BACKEND";
 const NEON_OPERATOR_KEYFILE: &str = "NEON_OPERATOR_KEYFILE";
 const NEON_ETH_MAX_AMOUNT: &str = "NEON_ETH_MAX_AMOUNT";
 const NEON_ETH_PER_TIME_MAX_AMOUNT: &str = "NEON_ETH_PER_TIME_MAX_AMOUNT";
 const NEON_ETH_TIME_SLICE_SECS: &str = "NEON_ETH_TIME_SLICE_SECS";
+const SOLANA_SOL_MODE: &str = "SOLANA_SOL_MODE";
+const SOLANA_SOL_MAX_AMOUNT: &str = "SOLANA_SOL_MAX_AMOUNT";
+const SOLANA_SOL_PER_TIME_MAX_AMOUNT: &str = "SOLANA_SOL_PER_TIME_MAX_AMOUNT";
 const NEON_LOG: &str = "NEON_LOG";
 const RUST_LOG: &str = "RUST_LOG";
 
@@ -110,19 +146,51 @@ static ENV: &[&str] = &[
     FAUCET_RPC_PORT,
     FAUCET_RPC_ALLOWED_ORIGINS,
     FAUCET_RPC_BLACKLISTED_IPS,
+    FAUCET_RPC_GRPC_PORT,
+    FAUCET_RPC_EVENTS_TOKEN,
+    FAUCET_RPC_ADMIN_TOKEN,
     FAUCET_WEB3_ENABLE,
     WEB3_RPC_URL,
+    WEB3_WS_URL,
+    WEB3_REQUEST_TIMEOUT_SECS,
     WEB3_PRIVATE_KEY,
     NEON_ERC20_TOKENS,
+    NEON_ERC20_TOKEN_ALIASES,
+    NEON_ERC20_TOKEN_MODES,
     NEON_ERC20_MAX_AMOUNT,
+    NEON_ERC20_PER_TIME_MAX_AMOUNT,
+    NEON_ERC20_TIME_SLICE_SECS,
+    NEON_ERC20_TOKENS_REFRESH_SECS,
+    WEB3_EIP1559,
+    WEB3_MAX_FEE_PER_GAS,
+    WEB3_MAX_PRIORITY_FEE_PER_GAS,
+    WEB3_GAS_MULTIPLIER,
+    WEB3_CONFIRMATIONS,
+    WEB3_STUCK_TIMEOUT_SECS,
+    WEB3_MAX_REPLACEMENTS,
+    WEB3_CANCEL_STUCK,
+    WEB3_FEE_BUMP_PERCENT,
+    WEB3_BATCH_CONTRACT,
+    WEB3_BATCH_WINDOW_MS,
     FAUCET_SOLANA_ENABLE,
     SOLANA_URL,
     SOLANA_COMMITMENT,
+    SOLANA_PREFLIGHT_COMMITMENT,
+    SOLANA_CONFIRM_COMMITMENT,
+    SOLANA_FIRE_AND_FORGET,
+    SOLANA_SIMULATE,
+    SOLANA_DURABLE_NONCE,
+    SOLANA_NONCE_ACCOUNTS,
+    SOLANA_NONCE_ACCOUNTS_COUNT,
     EVM_LOADER,
+    NEON_BACKEND,
     NEON_OPERATOR_KEYFILE,
     NEON_ETH_MAX_AMOUNT,
     NEON_ETH_PER_TIME_MAX_AMOUNT,
     NEON_ETH_TIME_SLICE_SECS,
+    SOLANA_SOL_MODE,
+    SOLANA_SOL_MAX_AMOUNT,
+    SOLANA_SOL_PER_TIME_MAX_AMOUNT,
     NEON_LOG,
     RUST_LOG,
 ];
@@ -164,21 +232,94 @@ pub fn load(file: &Path) -> Result<()> {
                 FAUCET_RPC_BLACKLISTED_IPS => {
                     CONFIG.write().unwrap().rpc.blacklisted_ips = parse_list_of_strings(&val)?
                 }
+                FAUCET_RPC_GRPC_PORT => {
+                    CONFIG.write().unwrap().rpc.grpc_port = val.parse::<u16>()?
+                }
+                FAUCET_RPC_EVENTS_TOKEN => CONFIG.write().unwrap().rpc.events_token = val,
+                FAUCET_RPC_ADMIN_TOKEN => CONFIG.write().unwrap().rpc.admin_token = val,
                 FAUCET_WEB3_ENABLE => CONFIG.write().unwrap().web3.enable = val.parse::<bool>()?,
                 WEB3_RPC_URL => CONFIG.write().unwrap().web3.rpc_url = val,
+                WEB3_WS_URL => CONFIG.write().unwrap().web3.ws_url = val,
+                WEB3_REQUEST_TIMEOUT_SECS => {
+                    CONFIG.write().unwrap().web3.request_timeout_secs = val.parse::<u64>()?
+                }
                 WEB3_PRIVATE_KEY => CONFIG.write().unwrap().web3.private_key = val,
                 NEON_ERC20_TOKENS => {
                     CONFIG.write().unwrap().web3.tokens = parse_list_of_strings(&val)?
                 }
+                NEON_ERC20_TOKEN_ALIASES => {
+                    CONFIG.write().unwrap().web3.token_aliases = parse_map_of_strings(&val)?
+                }
+                NEON_ERC20_TOKEN_MODES => {
+                    CONFIG.write().unwrap().web3.token_modes = parse_map_of_strings(&val)?
+                }
                 NEON_ERC20_MAX_AMOUNT => {
                     CONFIG.write().unwrap().web3.max_amount = val.parse::<u64>()?
                 }
+                NEON_ERC20_PER_TIME_MAX_AMOUNT => {
+                    CONFIG.write().unwrap().web3.per_time_max_amount = val.parse::<u64>()?
+                }
+                NEON_ERC20_TIME_SLICE_SECS => {
+                    CONFIG.write().unwrap().web3.time_slice_secs = val.parse::<u64>()?
+                }
+                NEON_ERC20_TOKENS_REFRESH_SECS => {
+                    CONFIG.write().unwrap().web3.tokens_refresh_secs = val.parse::<u64>()?
+                }
+                WEB3_EIP1559 => CONFIG.write().unwrap().web3.eip1559 = val.parse::<bool>()?,
+                WEB3_MAX_FEE_PER_GAS => {
+                    CONFIG.write().unwrap().web3.max_fee_per_gas = val.parse::<u64>()?
+                }
+                WEB3_MAX_PRIORITY_FEE_PER_GAS => {
+                    CONFIG.write().unwrap().web3.max_priority_fee_per_gas = val.parse::<u64>()?
+                }
+                WEB3_GAS_MULTIPLIER => {
+                    CONFIG.write().unwrap().web3.gas_multiplier = val.parse::<f64>()?
+                }
+                WEB3_CONFIRMATIONS => {
+                    CONFIG.write().unwrap().web3.confirmations = val.parse::<u64>()?
+                }
+                WEB3_STUCK_TIMEOUT_SECS => {
+                    CONFIG.write().unwrap().web3.stuck_timeout_secs = val.parse::<u64>()?
+                }
+                WEB3_MAX_REPLACEMENTS => {
+                    CONFIG.write().unwrap().web3.max_replacements = val.parse::<u32>()?
+                }
+                WEB3_CANCEL_STUCK => {
+                    CONFIG.write().unwrap().web3.cancel_stuck = val.parse::<bool>()?
+                }
+                WEB3_FEE_BUMP_PERCENT => {
+                    CONFIG.write().unwrap().web3.fee_bump_percent = val.parse::<u64>()?
+                }
+                WEB3_BATCH_CONTRACT => CONFIG.write().unwrap().web3.batch_contract = val,
+                WEB3_BATCH_WINDOW_MS => {
+                    CONFIG.write().unwrap().web3.batch_window_ms = val.parse::<u64>()?
+                }
                 FAUCET_SOLANA_ENABLE => {
                     CONFIG.write().unwrap().solana.enable = val.parse::<bool>()?
                 }
                 SOLANA_URL => CONFIG.write().unwrap().solana.url = val,
                 SOLANA_COMMITMENT => CONFIG.write().unwrap().solana.commitment = val,
+                SOLANA_PREFLIGHT_COMMITMENT => {
+                    CONFIG.write().unwrap().solana.preflight_commitment = val
+                }
+                SOLANA_CONFIRM_COMMITMENT => {
+                    CONFIG.write().unwrap().solana.confirm_commitment = val
+                }
+                SOLANA_FIRE_AND_FORGET => {
+                    CONFIG.write().unwrap().solana.fire_and_forget = val.parse::<bool>()?
+                }
+                SOLANA_SIMULATE => CONFIG.write().unwrap().solana.simulate = val.parse::<bool>()?,
+                SOLANA_DURABLE_NONCE => {
+                    CONFIG.write().unwrap().solana.durable_nonce = val.parse::<bool>()?
+                }
+                SOLANA_NONCE_ACCOUNTS => {
+                    CONFIG.write().unwrap().solana.nonce_accounts = parse_list_of_strings(&val)?
+                }
+                SOLANA_NONCE_ACCOUNTS_COUNT => {
+                    CONFIG.write().unwrap().solana.nonce_accounts_count = val.parse::<u64>()?
+                }
                 EVM_LOADER => CONFIG.write().unwrap().solana.evm_loader = val,
+                NEON_BACKEND => CONFIG.write().unwrap().solana.neon_backend = val,
                 NEON_OPERATOR_KEYFILE => {
                     CONFIG.write().unwrap().solana.operator_keyfile = val.into()
                 }
@@ -191,6 +332,13 @@ pub fn load(file: &Path) -> Result<()> {
                 NEON_ETH_TIME_SLICE_SECS => {
                     CONFIG.write().unwrap().solana.time_slice_secs = val.parse::<u64>()?
                 }
+                SOLANA_SOL_MODE => CONFIG.write().unwrap().solana.sol_mode = val,
+                SOLANA_SOL_MAX_AMOUNT => {
+                    CONFIG.write().unwrap().solana.sol_max_amount = val.parse::<u64>()?
+                }
+                SOLANA_SOL_PER_TIME_MAX_AMOUNT => {
+                    CONFIG.write().unwrap().solana.sol_per_time_max_amount = val.parse::<u64>()?
+                }
                 NEON_LOG => {}
                 RUST_LOG => {}
                 _ => unreachable!(),
@@ -198,6 +346,7 @@ pub fn load(file: &Path) -> Result<()> {
         }
     }
 
+    CONFIG.write().unwrap().solana.init_networks();
     CONFIG.read().unwrap().check()?;
 
     Ok(())
@@ -233,6 +382,21 @@ pub fn blacklisted_ips() -> Vec<String> {
     CONFIG.read().unwrap().rpc.blacklisted_ips.clone()
 }
 
+/// Gets the `rpc.grpc_port` value; 0 disables the gRPC server.
+pub fn rpc_grpc_port() -> u16 {
+    CONFIG.read().unwrap().rpc.grpc_port
+}
+
+/// Gets the `rpc.events_token` value; empty disables the global feed of events.
+pub fn rpc_events_token() -> String {
+    CONFIG.read().unwrap().rpc.events_token.clone()
+}
+
+/// Gets the `rpc.admin_token` value; empty disables admin operations over gRPC.
+pub fn rpc_admin_token() -> String {
+    CONFIG.read().unwrap().rpc.admin_token.clone()
+}
+
 /// Gets the `web3.enable` value.
 pub fn web3_enabled() -> bool {
     CONFIG.read().unwrap().web3.enable
@@ -243,6 +407,24 @@ pub fn web3_rpc_url() -> String {
     CONFIG.read().unwrap().web3.rpc_url.clone()
 }
 
+/// Gets the `web3.ws_url` value; empty means receipts are polled over `web3.rpc_url`.
+pub fn web3_ws_url() -> String {
+    CONFIG.read().unwrap().web3.ws_url.clone()
+}
+
+/// Default timeout of a single request to the Ethereum endpoint.
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// Gets the `web3.request_timeout_secs` value.
+pub fn web3_request_timeout_secs() -> u64 {
+    let timeout = CONFIG.read().unwrap().web3.request_timeout_secs;
+    if timeout > 0 {
+        timeout
+    } else {
+        DEFAULT_REQUEST_TIMEOUT_SECS
+    }
+}
+
 /// Gets the `web3.private_key` value. Removes prefix 0x if any.
 pub fn web3_private_key() -> String {
     let key = &CONFIG.read().unwrap().web3.private_key;
@@ -254,11 +436,184 @@ pub fn tokens() -> Vec<String> {
     CONFIG.read().unwrap().web3.tokens.clone()
 }
 
+/// Gets the `web3.token_aliases` value.
+pub fn web3_token_aliases() -> HashMap<String, String> {
+    CONFIG.read().unwrap().web3.token_aliases.clone()
+}
+
+pub const TOKEN_MODE_TRANSFER: &str = "transfer";
+pub const TOKEN_MODE_MINT: &str = "mint";
+
+/// Gets the `web3.token_modes` value of the token (`transfer` by default).
+pub fn web3_token_mode(token: &str) -> String {
+    let config = CONFIG.read().unwrap();
+    let address = ethereum::address_from_str(token).ok();
+    config
+        .web3
+        .token_modes
+        .iter()
+        .find(|(t, _)| address.is_some() && ethereum::address_from_str(t).ok() == address)
+        .map_or_else(|| TOKEN_MODE_TRANSFER.into(), |(_, mode)| mode.clone())
+}
+
+pub const NFT_ERC721: &str = "erc721";
+pub const NFT_ERC1155: &str = "erc1155";
+pub const NFT_MODE_MINT: &str = "mint";
+pub const NFT_MODE_INVENTORY: &str = "inventory";
+
+/// Gets addresses of the NFT collections.
+pub fn web3_nfts() -> Vec<String> {
+    let config = CONFIG.read().unwrap();
+    config.web3.nfts.iter().map(|n| n.address.clone()).collect()
+}
+
+/// Gets the `web3.nfts.standard` value of the NFT collection.
+pub fn web3_nft_standard(address: &str) -> String {
+    with_nft(address, |n| n.standard.clone())
+}
+
+/// Gets the `web3.nfts.mode` value of the NFT collection.
+pub fn web3_nft_mode(address: &str) -> String {
+    with_nft(address, |n| n.mode.clone())
+}
+
+/// Gets the `web3.nfts.token_ids` value of the NFT collection.
+pub fn web3_nft_token_ids(address: &str) -> Vec<u64> {
+    with_nft(address, |n| n.token_ids.clone())
+}
+
+/// Gets the `web3.nfts.max_amount` value of the NFT collection (1 by default).
+pub fn web3_nft_max_amount(address: &str) -> u64 {
+    with_nft(address, |n| n.max_amount.max(1))
+}
+
+/// Gets the `web3.nfts.per_wallet_max_amount` value of the NFT collection.
+pub fn web3_nft_per_wallet_max_amount(address: &str) -> u64 {
+    with_nft(address, |n| n.per_wallet_max_amount)
+}
+
+/// Calls `f` with parameters of the NFT collection (or defaults if it's unknown).
+fn with_nft<R>(address: &str, f: impl FnOnce(&Nft) -> R) -> R {
+    let config = CONFIG.read().unwrap();
+    match config.web3.nfts.iter().find(|n| n.address == address) {
+        Some(n) => f(n),
+        None => f(&Nft::default()),
+    }
+}
+
 /// Gets the `web3.max_amount` value.
 pub fn web3_max_amount() -> u64 {
     CONFIG.read().unwrap().web3.max_amount
 }
 
+/// Gets the `web3.per_time_max_amount` value; 0 means no cap.
+pub fn web3_per_time_max_amount() -> u64 {
+    CONFIG.read().unwrap().web3.per_time_max_amount
+}
+
+/// Gets the `web3.time_slice_secs` value.
+pub fn web3_time_slice_secs() -> u64 {
+    CONFIG.read().unwrap().web3.time_slice_secs
+}
+
+/// Default interval of re-validation of ERC20 tokens.
+const DEFAULT_TOKENS_REFRESH_SECS: u64 = 600;
+
+/// Gets the `web3.tokens_refresh_secs` value.
+pub fn web3_tokens_refresh_secs() -> u64 {
+    let interval = CONFIG.read().unwrap().web3.tokens_refresh_secs;
+    if interval > 0 {
+        interval
+    } else {
+        DEFAULT_TOKENS_REFRESH_SECS
+    }
+}
+
+/// Gets the `web3.eip1559` value.
+pub fn web3_eip1559() -> bool {
+    CONFIG.read().unwrap().web3.eip1559
+}
+
+/// Gets the `web3.max_fee_per_gas` value (in wei); 0 means no cap.
+pub fn web3_max_fee_per_gas() -> u64 {
+    CONFIG.read().unwrap().web3.max_fee_per_gas
+}
+
+/// Gets the `web3.max_priority_fee_per_gas` value (in wei); 0 means no cap.
+pub fn web3_max_priority_fee_per_gas() -> u64 {
+    CONFIG.read().unwrap().web3.max_priority_fee_per_gas
+}
+
+/// Default safety multiplier of estimated gas.
+const DEFAULT_GAS_MULTIPLIER: f64 = 1.25;
+
+/// Gets the `web3.gas_multiplier` value.
+pub fn web3_gas_multiplier() -> f64 {
+    let multiplier = CONFIG.read().unwrap().web3.gas_multiplier;
+    if multiplier > 0.0 {
+        multiplier
+    } else {
+        DEFAULT_GAS_MULTIPLIER
+    }
+}
+
+/// Gets the `web3.confirmations` value.
+pub fn web3_confirmations() -> u64 {
+    CONFIG.read().unwrap().web3.confirmations
+}
+
+/// Default timeout after which a pending transaction is considered stuck.
+const DEFAULT_STUCK_TIMEOUT_SECS: u64 = 60;
+
+/// Gets the `web3.stuck_timeout_secs` value.
+pub fn web3_stuck_timeout_secs() -> u64 {
+    let timeout = CONFIG.read().unwrap().web3.stuck_timeout_secs;
+    if timeout > 0 {
+        timeout
+    } else {
+        DEFAULT_STUCK_TIMEOUT_SECS
+    }
+}
+
+/// Gets the `web3.max_replacements` value.
+pub fn web3_max_replacements() -> u32 {
+    CONFIG.read().unwrap().web3.max_replacements
+}
+
+/// Gets the `web3.cancel_stuck` value.
+pub fn web3_cancel_stuck() -> bool {
+    CONFIG.read().unwrap().web3.cancel_stuck
+}
+
+/// Default fee increase of a replacement transaction.
+/// Nodes reject replacements with bumps below 10%.
+const DEFAULT_FEE_BUMP_PERCENT: u64 = 25;
+
+/// Gets the `web3.fee_bump_percent` value.
+pub fn web3_fee_bump_percent() -> u64 {
+    let percent = CONFIG.read().unwrap().web3.fee_bump_percent;
+    if percent > 0 {
+        percent
+    } else {
+        DEFAULT_FEE_BUMP_PERCENT
+    }
+}
+
+/// Checks if ERC20 transfers are batched through the `web3.batch_contract`.
+pub fn web3_batch_enabled() -> bool {
+    !CONFIG.read().unwrap().web3.batch_contract.is_empty()
+}
+
+/// Gets the `web3.batch_contract` value.
+pub fn web3_batch_contract() -> String {
+    CONFIG.read().unwrap().web3.batch_contract.clone()
+}
+
+/// Gets the `web3.batch_window_ms` value.
+pub fn web3_batch_window_ms() -> u64 {
+    CONFIG.read().unwrap().web3.batch_window_ms
+}
+
 /// Gets the `solana.enable` value.
 pub fn solana_enabled() -> bool {
     CONFIG.read().unwrap().solana.enable
@@ -269,40 +624,131 @@ pub fn solana_url() -> String {
     CONFIG.read().unwrap().solana.url.clone()
 }
 
-/// Gets the `solana.commitment` value.
+/// Gets the `solana.commitment` value (used for reads).
 pub fn solana_commitment() -> CommitmentConfig {
     let commitment = &CONFIG.read().unwrap().solana.commitment;
-    match commitment.as_ref() {
-        "processed" => CommitmentConfig::processed(),
-        "confirmed" => CommitmentConfig::confirmed(),
-        "finalized" => CommitmentConfig::finalized(),
-        _ => {
-            error!("Unexpected commitment level '{}'", commitment);
-            error!("Allowed levels: 'processed', 'confirmed' or 'finalized'");
-            warn!("The default level 'finalized' will be used");
-            CommitmentConfig::default()
-        }
+    parse_commitment(commitment).unwrap_or_else(|| {
+        error!("Unexpected commitment level '{}'", commitment);
+        error!("Allowed levels: 'processed', 'confirmed' or 'finalized'");
+        warn!("The default level 'finalized' will be used");
+        CommitmentConfig::default()
+    })
+}
+
+/// Gets the `solana.preflight_commitment` value; `solana.commitment` if empty.
+pub fn solana_preflight_commitment() -> CommitmentConfig {
+    let commitment = CONFIG.read().unwrap().solana.preflight_commitment.clone();
+    parse_commitment(&commitment).unwrap_or_else(solana_commitment)
+}
+
+/// Gets the `solana.confirm_commitment` value; `solana.commitment` if empty.
+pub fn solana_confirm_commitment() -> CommitmentConfig {
+    let commitment = CONFIG.read().unwrap().solana.confirm_commitment.clone();
+    parse_commitment(&commitment).unwrap_or_else(solana_commitment)
+}
+
+/// Gets the `solana.fire_and_forget` value.
+pub fn solana_fire_and_forget() -> bool {
+    CONFIG.read().unwrap().solana.fire_and_forget
+}
+
+/// Parses commitment level name.
+pub fn parse_commitment(commitment: &str) -> Option<CommitmentConfig> {
+    match commitment {
+        "processed" => Some(CommitmentConfig::processed()),
+        "confirmed" => Some(CommitmentConfig::confirmed()),
+        "finalized" => Some(CommitmentConfig::finalized()),
+        _ => None,
     }
 }
 
-/// Gets the `solana.evm_loader` address value.
-pub fn solana_evm_loader() -> String {
-    CONFIG.read().unwrap().solana.evm_loader.clone()
+#[test]
+fn test_parse_commitment() {
+    assert_eq!(parse_commitment(""), None);
+    assert_eq!(parse_commitment("max"), None);
+    assert_eq!(
+        parse_commitment("processed"),
+        Some(CommitmentConfig::processed())
+    );
+    assert_eq!(
+        parse_commitment("confirmed"),
+        Some(CommitmentConfig::confirmed())
+    );
+    assert_eq!(
+        parse_commitment("finalized"),
+        Some(CommitmentConfig::finalized())
+    );
+}
+
+/// Gets the `solana.simulate` value.
+pub fn solana_simulate() -> bool {
+    CONFIG.read().unwrap().solana.simulate
+}
+
+/// Gets the `solana.durable_nonce` value.
+pub fn solana_durable_nonce() -> bool {
+    CONFIG.read().unwrap().solana.durable_nonce
+}
+
+/// Gets the `solana.nonce_accounts` addresses.
+pub fn solana_nonce_accounts() -> Vec<String> {
+    CONFIG.read().unwrap().solana.nonce_accounts.clone()
+}
+
+/// Gets the `solana.nonce_accounts_count` value.
+pub fn solana_nonce_accounts_count() -> u64 {
+    CONFIG.read().unwrap().solana.nonce_accounts_count
+}
+
+/// Gets the `solana.evm_loader` address value of the `network`.
+pub fn solana_evm_loader(network: &str) -> String {
+    with_network(network, |n| n.evm_loader.clone())
+}
+
+/// Checks if `solana.neon_backend` is `evm` (NEON is sent by EVM transactions).
+pub fn solana_evm_backend() -> bool {
+    CONFIG.read().unwrap().solana.evm_backend()
+}
+
+/// Gets the `solana.account_seed_version` value of the `network`.
+pub fn solana_account_seed_version(network: &str) -> u8 {
+    with_network(network, |n| n.account_seed_version)
+}
+
+/// Gets the `solana.token_mint` address value of the `network`.
+pub fn solana_token_mint_id(network: &str) -> String {
+    with_network(network, |n| n.token_mint.clone())
+}
+
+/// Gets the `solana.token_mint_decimals` value of the `network`.
+pub fn solana_token_mint_decimals(network: &str) -> u8 {
+    with_network(network, |n| n.token_mint_decimals)
 }
 
-/// Gets the `solana.account_seed_version` value.
-pub fn solana_account_seed_version() -> u8 {
-    CONFIG.read().unwrap().solana.account_seed_version
+/// Gets names of the Neon EVM networks; the default one goes first.
+pub fn solana_networks() -> Vec<String> {
+    let config = CONFIG.read().unwrap();
+    config
+        .solana
+        .networks
+        .iter()
+        .map(|n| n.name.clone())
+        .collect()
 }
 
-/// Gets the `solana.token_mint` address value.
-pub fn solana_token_mint_id() -> String {
-    CONFIG.read().unwrap().solana.token_mint.clone()
+/// Checks if the Neon EVM `network` is configured.
+pub fn solana_network_exists(network: &str) -> bool {
+    let config = CONFIG.read().unwrap();
+    config.solana.networks.iter().any(|n| n.name == network)
 }
 
-/// Gets the `solana.token_mint_decimals` value.
-pub fn solana_token_mint_decimals() -> u8 {
-    CONFIG.read().unwrap().solana.token_mint_decimals
+/// Calls `f` with parameters of the `network` (or defaults if it's unknown).
+fn with_network<R>(network: &str, f: impl FnOnce(&Network) -> R) -> R {
+    let config = CONFIG.read().unwrap();
+    match config.solana.networks.iter().find(|n| n.name == network) {
+        Some(n) => f(n),
+        None => f(&Network::default()),
+    }
 }
 
 /// Gets the `solana.operator` keypair value.
@@ -321,19 +767,90 @@ pub fn solana_operator_keypair() -> Result<Keypair> {
     Ok(Keypair::from_bytes(&bytes)?)
 }
 
-/// Gets the `solana.max_amount` value
-pub fn solana_max_amount() -> u64 {
-    CONFIG.read().unwrap().solana.max_amount
+/// Gets the `solana.max_amount` value of the `network`.
+pub fn solana_max_amount(network: &str) -> u64 {
+    with_network(network, |n| n.max_amount)
+}
+
+/// Gets the `solana.per_time_max_amount` value of the `network`.
+pub fn solana_per_time_max_amount(network: &str) -> u64 {
+    with_network(network, |n| n.per_time_max_amount)
+}
+
+/// Gets the `solana.time_slice_secs` value of the `network`.
+pub fn solana_time_slice_secs(network: &str) -> u64 {
+    with_network(network, |n| n.time_slice_secs)
+}
+
+/// Gets the `solana.networks.evm_confirmations` value of the `network`;
+/// `web3.confirmations` for the default network or if it's missing.
+pub fn solana_evm_confirmations(network: &str) -> u64 {
+    with_network(network, |n| n.evm_confirmations).unwrap_or_else(web3_confirmations)
+}
+
+/// SOL is requested with the `requestAirdrop` RPC, or sent by the operator if it fails.
+pub const SOL_MODE_AUTO: &str = "auto";
+/// SOL is requested with the `requestAirdrop` RPC (test validators only).
+pub const SOL_MODE_AIRDROP: &str = "airdrop";
+/// SOL is sent from the operator account.
+pub const SOL_MODE_TRANSFER: &str = "transfer";
+
+/// Gets the `solana.sol_mode` value.
+pub fn solana_sol_mode() -> String {
+    let mode = CONFIG.read().unwrap().solana.sol_mode.clone();
+    if mode.is_empty() {
+        SOL_MODE_AUTO.into()
+    } else {
+        mode
+    }
+}
+
+/// Gets the `solana.sol_max_amount` value; 0 means SOL airdrops are disabled.
+pub fn solana_sol_max_amount() -> u64 {
+    CONFIG.read().unwrap().solana.sol_max_amount
+}
+
+/// Gets the `solana.sol_per_time_max_amount` value.
+pub fn solana_sol_per_time_max_amount() -> u64 {
+    CONFIG.read().unwrap().solana.sol_per_time_max_amount
+}
+
+/// Mint of an SPL token entry which stands for wrapped NEON of the default network.
+pub const SPL_NEON: &str = "NEON";
+
+/// Gets mints of the SPL tokens.
+pub fn solana_spl_tokens() -> Vec<String> {
+    let config = CONFIG.read().unwrap();
+    config
+        .solana
+        .spl_tokens
+        .iter()
+        .map(|t| t.mint.clone())
+        .collect()
+}
+
+/// Gets the `solana.spl_tokens.symbol` value of the SPL token.
+pub fn solana_spl_token_symbol(mint: &str) -> String {
+    with_spl_token(mint, |t| t.symbol.clone())
+}
+
+/// Gets the `solana.spl_tokens.max_amount` value of the SPL token.
+pub fn solana_spl_token_max_amount(mint: &str) -> u64 {
+    with_spl_token(mint, |t| t.max_amount)
 }
 
-/// Gets the `solana.per_time_max_amount` value
-pub fn solana_per_time_max_amount() -> u64 {
-    CONFIG.read().unwrap().solana.per_time_max_amount
+/// Gets the `solana.spl_tokens.per_time_max_amount` value of the SPL token.
+pub fn solana_spl_token_per_time_max_amount(mint: &str) -> u64 {
+    with_spl_token(mint, |t| t.per_time_max_amount)
 }
 
-/// Gets the `solana.time_slice_secs` value
-pub fn solana_time_slice_secs() -> u64 {
-    CONFIG.read().unwrap().solana.time_slice_secs
+/// Calls `f` with parameters of the SPL token (or defaults if it's unknown).
+fn with_spl_token<R>(mint: &str, f: impl FnOnce(&SplToken) -> R) -> R {
+    let config = CONFIG.read().unwrap();
+    match config.solana.spl_tokens.iter().find(|t| t.mint == mint) {
+        Some(t) => f(t),
+        None => f(&SplToken::default()),
+    }
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -344,6 +861,9 @@ struct Rpc {
     port: u16,
     allowed_origins: Vec<String>,
     blacklisted_ips: Vec<String>,
+    grpc_port: u16,
+    events_token: String,
+    admin_token: String,
 }
 
 impl Rpc {
@@ -354,6 +874,12 @@ impl Rpc {
         if self.port == 0 {
             return Err(Error::InvalidParameter("rpc.port".into(), "0".into()));
         }
+        if self.grpc_port == self.port {
+            return Err(Error::InvalidParameter(
+                "rpc.grpc_port".into(),
+                self.grpc_port.to_string(),
+            ));
+        }
         Ok(())
     }
 }
@@ -380,7 +906,33 @@ impl std::fmt::Display for Rpc {
         }
         write!(f, "rpc.blacklisted_ips = {:?}", self.blacklisted_ips)?;
         if env::var(FAUCET_RPC_BLACKLISTED_IPS).is_ok() {
-            write!(f, " (overridden by {})", FAUCET_RPC_BLACKLISTED_IPS)
+            writeln!(f, " (overridden by {})", FAUCET_RPC_BLACKLISTED_IPS)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(f, "rpc.grpc_port = {}", self.grpc_port)?;
+        if env::var(FAUCET_RPC_GRPC_PORT).is_ok() {
+            writeln!(f, " (overridden by {})", FAUCET_RPC_GRPC_PORT)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(
+            f,
+            "rpc.events_token = \"{}\"",
+            obfuscate_string(&self.events_token)
+        )?;
+        if env::var(FAUCET_RPC_EVENTS_TOKEN).is_ok() {
+            writeln!(f, " (overridden by {})", FAUCET_RPC_EVENTS_TOKEN)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(
+            f,
+            "rpc.admin_token = \"{}\"",
+            obfuscate_string(&self.admin_token)
+        )?;
+        if env::var(FAUCET_RPC_ADMIN_TOKEN).is_ok() {
+            write!(f, " (overridden by {})", FAUCET_RPC_ADMIN_TOKEN)
         } else {
             write!(f, "")
         }
@@ -393,12 +945,40 @@ impl std::fmt::Display for Rpc {
 struct Web3 {
     enable: bool,
     rpc_url: String,
+    ws_url: String,
+    request_timeout_secs: u64,
     private_key: String,
     tokens: Vec<String>,
+    token_aliases: HashMap<String, String>,
+    token_modes: HashMap<String, String>,
     max_amount: u64,
+    per_time_max_amount: u64,
+    time_slice_secs: u64,
+    tokens_refresh_secs: u64,
+    eip1559: bool,
+    max_fee_per_gas: u64,
+    max_priority_fee_per_gas: u64,
+    gas_multiplier: f64,
+    confirmations: u64,
+    stuck_timeout_secs: u64,
+    max_replacements: u32,
+    cancel_stuck: bool,
+    fee_bump_percent: u64,
+    batch_contract: String,
+    batch_window_ms: u64,
+    nfts: Vec<Nft>,
 }
 
 impl Web3 {
+    /// Checks if the address is one of `tokens`.
+    fn is_known_token(&self, address: &str) -> bool {
+        ethereum::address_from_str(address).map_or(false, |a| {
+            self.tokens
+                .iter()
+                .any(|t| ethereum::address_from_str(t).ok() == Some(a))
+        })
+    }
+
     fn check(&self) -> Result<()> {
         if self.enable {
             if self.rpc_url.is_empty() {
@@ -419,12 +999,69 @@ impl Web3 {
                     "<empty>".into(),
                 ));
             }
+            for (alias, address) in &self.token_aliases {
+                if !self.is_known_token(address) {
+                    return Err(Error::InvalidParameter(
+                        format!("web3.token_aliases.{}", alias),
+                        address.clone(),
+                    ));
+                }
+            }
+            for (address, mode) in &self.token_modes {
+                if !self.is_known_token(address) {
+                    return Err(Error::InvalidParameter(
+                        "web3.token_modes".into(),
+                        address.clone(),
+                    ));
+                }
+                if !matches!(mode.as_str(), TOKEN_MODE_TRANSFER | TOKEN_MODE_MINT | AUTO) {
+                    return Err(Error::InvalidParameter(
+                        format!("web3.token_modes.{}", address),
+                        mode.clone(),
+                    ));
+                }
+            }
             if self.max_amount == 0 {
                 return Err(Error::InvalidParameter(
                     "web3.max_amount".into(),
                     "0".into(),
                 ));
             }
+            if self.per_time_max_amount > 0 && self.time_slice_secs == 0 {
+                return Err(Error::InvalidParameter(
+                    "web3.time_slice_secs".into(),
+                    "0".into(),
+                ));
+            }
+            if self.gas_multiplier < 0.0 {
+                return Err(Error::InvalidParameter(
+                    "web3.gas_multiplier".into(),
+                    self.gas_multiplier.to_string(),
+                ));
+            }
+            if self.fee_bump_percent > 0 && self.fee_bump_percent < 10 {
+                return Err(Error::InvalidParameter(
+                    "web3.fee_bump_percent".into(),
+                    self.fee_bump_percent.to_string(),
+                ));
+            }
+            if !self.batch_contract.is_empty()
+                && ethereum::address_from_str(&self.batch_contract).is_err()
+            {
+                return Err(Error::InvalidParameter(
+                    "web3.batch_contract".into(),
+                    self.batch_contract.clone(),
+                ));
+            }
+            for nft in &self.nfts {
+                nft.check()?;
+            }
+            if !self.nfts.is_empty() && self.time_slice_secs == 0 {
+                return Err(Error::InvalidParameter(
+                    "web3.time_slice_secs".into(),
+                    "0".into(),
+                ));
+            }
         }
         Ok(())
     }
@@ -448,6 +1085,22 @@ impl std::fmt::Display for Web3 {
         } else {
             writeln!(f)?;
         }
+        write!(f, "web3.ws_url = \"{}\"", self.ws_url)?;
+        if env::var(WEB3_WS_URL).is_ok() {
+            writeln!(f, " (overridden by {})", WEB3_WS_URL)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(
+            f,
+            "web3.request_timeout_secs = {}",
+            self.request_timeout_secs
+        )?;
+        if env::var(WEB3_REQUEST_TIMEOUT_SECS).is_ok() {
+            writeln!(f, " (overridden by {})", WEB3_REQUEST_TIMEOUT_SECS)?;
+        } else {
+            writeln!(f)?;
+        }
         write!(
             f,
             "web3.private_key = \"{}\"",
@@ -468,12 +1121,186 @@ impl std::fmt::Display for Web3 {
         } else {
             writeln!(f)?;
         }
+        write!(f, "web3.token_aliases = {:?}", self.token_aliases)?;
+        if env::var(NEON_ERC20_TOKEN_ALIASES).is_ok() {
+            writeln!(f, " (overridden by {})", NEON_ERC20_TOKEN_ALIASES)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(f, "web3.token_modes = {:?}", self.token_modes)?;
+        if env::var(NEON_ERC20_TOKEN_MODES).is_ok() {
+            writeln!(f, " (overridden by {})", NEON_ERC20_TOKEN_MODES)?;
+        } else {
+            writeln!(f)?;
+        }
         write!(f, "web3.max_amount = {}", self.max_amount)?;
         if env::var(NEON_ERC20_MAX_AMOUNT).is_ok() {
-            write!(f, " (overridden by {})", NEON_ERC20_MAX_AMOUNT)
+            writeln!(f, " (overridden by {})", NEON_ERC20_MAX_AMOUNT)?;
         } else {
-            write!(f, "")
+            writeln!(f)?;
+        }
+        write!(f, "web3.per_time_max_amount = {}", self.per_time_max_amount)?;
+        if env::var(NEON_ERC20_PER_TIME_MAX_AMOUNT).is_ok() {
+            writeln!(f, " (overridden by {})", NEON_ERC20_PER_TIME_MAX_AMOUNT)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(f, "web3.time_slice_secs = {}", self.time_slice_secs)?;
+        if env::var(NEON_ERC20_TIME_SLICE_SECS).is_ok() {
+            writeln!(f, " (overridden by {})", NEON_ERC20_TIME_SLICE_SECS)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(f, "web3.tokens_refresh_secs = {}", self.tokens_refresh_secs)?;
+        if env::var(NEON_ERC20_TOKENS_REFRESH_SECS).is_ok() {
+            writeln!(f, " (overridden by {})", NEON_ERC20_TOKENS_REFRESH_SECS)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(f, "web3.eip1559 = {}", self.eip1559)?;
+        if env::var(WEB3_EIP1559).is_ok() {
+            writeln!(f, " (overridden by {})", WEB3_EIP1559)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(f, "web3.max_fee_per_gas = {}", self.max_fee_per_gas)?;
+        if env::var(WEB3_MAX_FEE_PER_GAS).is_ok() {
+            writeln!(f, " (overridden by {})", WEB3_MAX_FEE_PER_GAS)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(
+            f,
+            "web3.max_priority_fee_per_gas = {}",
+            self.max_priority_fee_per_gas
+        )?;
+        if env::var(WEB3_MAX_PRIORITY_FEE_PER_GAS).is_ok() {
+            writeln!(f, " (overridden by {})", WEB3_MAX_PRIORITY_FEE_PER_GAS)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(f, "web3.gas_multiplier = {}", self.gas_multiplier)?;
+        if env::var(WEB3_GAS_MULTIPLIER).is_ok() {
+            writeln!(f, " (overridden by {})", WEB3_GAS_MULTIPLIER)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(f, "web3.confirmations = {}", self.confirmations)?;
+        if env::var(WEB3_CONFIRMATIONS).is_ok() {
+            writeln!(f, " (overridden by {})", WEB3_CONFIRMATIONS)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(f, "web3.stuck_timeout_secs = {}", self.stuck_timeout_secs)?;
+        if env::var(WEB3_STUCK_TIMEOUT_SECS).is_ok() {
+            writeln!(f, " (overridden by {})", WEB3_STUCK_TIMEOUT_SECS)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(f, "web3.max_replacements = {}", self.max_replacements)?;
+        if env::var(WEB3_MAX_REPLACEMENTS).is_ok() {
+            writeln!(f, " (overridden by {})", WEB3_MAX_REPLACEMENTS)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(f, "web3.cancel_stuck = {}", self.cancel_stuck)?;
+        if env::var(WEB3_CANCEL_STUCK).is_ok() {
+            writeln!(f, " (overridden by {})", WEB3_CANCEL_STUCK)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(f, "web3.fee_bump_percent = {}", self.fee_bump_percent)?;
+        if env::var(WEB3_FEE_BUMP_PERCENT).is_ok() {
+            writeln!(f, " (overridden by {})", WEB3_FEE_BUMP_PERCENT)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(f, "web3.batch_contract = \"{}\"", self.batch_contract)?;
+        if env::var(WEB3_BATCH_CONTRACT).is_ok() {
+            writeln!(f, " (overridden by {})", WEB3_BATCH_CONTRACT)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(f, "web3.batch_window_ms = {}", self.batch_window_ms)?;
+        if env::var(WEB3_BATCH_WINDOW_MS).is_ok() {
+            write!(f, " (overridden by {})", WEB3_BATCH_WINDOW_MS)?;
+        } else {
+            write!(f, "")?;
+        }
+        for nft in &self.nfts {
+            writeln!(f)?;
+            write!(f, "{}", nft)?;
+        }
+        Ok(())
+    }
+}
+
+/// Represents an NFT collection dispensed by the faucet.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+struct Nft {
+    address: String,
+    standard: String,
+    mode: String,
+    token_ids: Vec<u64>,
+    max_amount: u64,
+    per_wallet_max_amount: u64,
+}
+
+impl Nft {
+    fn check(&self) -> Result<()> {
+        if ethereum::address_from_str(&self.address).is_err() {
+            return Err(Error::InvalidParameter(
+                "web3.nfts.address".into(),
+                self.address.clone(),
+            ));
+        }
+        let prefix = format!("web3.nfts.{}", self.address);
+        if !matches!(self.standard.as_str(), NFT_ERC721 | NFT_ERC1155) {
+            return Err(Error::InvalidParameter(
+                format!("{}.standard", prefix),
+                self.standard.clone(),
+            ));
+        }
+        if !matches!(
+            self.mode.as_str(),
+            "" | AUTO | NFT_MODE_MINT | NFT_MODE_INVENTORY
+        ) {
+            return Err(Error::InvalidParameter(
+                format!("{}.mode", prefix),
+                self.mode.clone(),
+            ));
+        }
+        let needs_token_ids = self.standard == NFT_ERC1155 || self.mode != NFT_MODE_MINT;
+        if needs_token_ids && self.token_ids.is_empty() {
+            return Err(Error::InvalidParameter(
+                format!("{}.token_ids", prefix),
+                "<empty>".into(),
+            ));
         }
+        if self.per_wallet_max_amount == 0 {
+            return Err(Error::InvalidParameter(
+                format!("{}.per_wallet_max_amount", prefix),
+                "0".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Nft {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let prefix = format!("web3.nfts.{}", self.address);
+        writeln!(f, "{}.standard = {:?}", prefix, self.standard)?;
+        writeln!(f, "{}.mode = {:?}", prefix, self.mode)?;
+        writeln!(f, "{}.token_ids = {:?}", prefix, self.token_ids)?;
+        writeln!(f, "{}.max_amount = {}", prefix, self.max_amount)?;
+        write!(
+            f,
+            "{}.per_wallet_max_amount = {}",
+            prefix, self.per_wallet_max_amount
+        )
     }
 }
 
@@ -484,7 +1311,15 @@ struct Solana {
     enable: bool,
     url: String,
     commitment: String,
+    preflight_commitment: String,
+    confirm_commitment: String,
+    fire_and_forget: bool,
+    simulate: bool,
+    durable_nonce: bool,
+    nonce_accounts: Vec<String>,
+    nonce_accounts_count: u64,
     evm_loader: String,
+    neon_backend: String,
     account_seed_version: u8,           // from neon params
     token_mint: String,                 // from neon params
     token_mint_decimals: u8,            // from neon params
@@ -495,41 +1330,44 @@ struct Solana {
     max_amount: u64,
     per_time_max_amount: u64,
     time_slice_secs: u64,
+    networks: Vec<Network>,
+    spl_tokens: Vec<SplToken>,
+    sol_mode: String,
+    sol_max_amount: u64,
+    sol_per_time_max_amount: u64,
 }
 
 impl Solana {
+    /// Puts the default network (top-level parameters) in front of the list.
+    fn init_networks(&mut self) {
+        self.networks.retain(|n| n.name != DEFAULT_NETWORK);
+        let default = Network {
+            name: DEFAULT_NETWORK.into(),
+            evm_loader: self.evm_loader.clone(),
+            account_seed_version: self.account_seed_version,
+            token_mint: self.token_mint.clone(),
+            token_mint_decimals: self.token_mint_decimals,
+            compute_budget_units: self.compute_budget_units,
+            compute_budget_heap_frame: self.compute_budget_heap_frame,
+            compute_budget_additional_fee: self.compute_budget_additional_fee,
+            max_amount: self.max_amount,
+            per_time_max_amount: self.per_time_max_amount,
+            time_slice_secs: self.time_slice_secs,
+            evm_confirmations: None,
+        };
+        self.networks.insert(0, default);
+    }
+
     fn check(&self) -> Result<()> {
         if self.enable {
-            if self.url.is_empty() {
-                return Err(Error::InvalidParameter(
-                    "solana.url".into(),
-                    "<empty>".into(),
-                ));
-            }
-            if self.commitment.is_empty() {
-                return Err(Error::InvalidParameter(
-                    "solana.commitment".into(),
-                    "<empty>".into(),
-                ));
-            }
-            if self.evm_loader.is_empty() {
-                return Err(Error::InvalidParameter(
-                    "solana.evm_loader".into(),
-                    "<empty>".into(),
-                ));
-            }
-            if Pubkey::from_str(&self.evm_loader).is_err() {
+            if !["", "solana", "evm"].contains(&self.neon_backend.as_str()) {
                 return Err(Error::InvalidParameter(
-                    "solana.evm_loader".into(),
-                    self.evm_loader.clone(),
+                    "solana.neon_backend".into(),
+                    self.neon_backend.clone(),
                 ));
             }
-            use nix::NixPath as _; // to check if PathBuf is empty
-            if self.operator_keyfile.is_empty() {
-                return Err(Error::InvalidParameter(
-                    "solana.operator_keyfile".into(),
-                    "<empty>".into(),
-                ));
+            if !self.evm_backend() || !self.spl_tokens.is_empty() || self.sol_max_amount > 0 {
+                self.check_solana_access()?;
             }
             if self.max_amount == 0 {
                 return Err(Error::InvalidParameter(
@@ -549,9 +1387,102 @@ impl Solana {
                     "0".into(),
                 ));
             }
+            for (i, network) in self.networks.iter().enumerate() {
+                if self.networks[..i].iter().any(|n| n.name == network.name) {
+                    return Err(Error::InvalidParameter(
+                        "solana.networks.name".into(),
+                        network.name.clone(),
+                    ));
+                }
+                network.check(!self.evm_backend())?;
+            }
+            for token in &self.spl_tokens {
+                token.check()?;
+            }
+            if !["", SOL_MODE_AUTO, SOL_MODE_AIRDROP, SOL_MODE_TRANSFER]
+                .contains(&self.sol_mode.as_str())
+            {
+                return Err(Error::InvalidParameter(
+                    "solana.sol_mode".into(),
+                    self.sol_mode.clone(),
+                ));
+            }
+            if self.sol_max_amount > 0 && self.sol_per_time_max_amount == 0 {
+                return Err(Error::InvalidParameter(
+                    "solana.sol_per_time_max_amount".into(),
+                    "0".into(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks parameters needed to send transactions into Solana.
+    fn check_solana_access(&self) -> Result<()> {
+        if self.url.is_empty() {
+            return Err(Error::InvalidParameter(
+                "solana.url".into(),
+                "<empty>".into(),
+            ));
+        }
+        if self.commitment.is_empty() {
+            return Err(Error::InvalidParameter(
+                "solana.commitment".into(),
+                "<empty>".into(),
+            ));
+        }
+        if self.evm_loader.is_empty() {
+            return Err(Error::InvalidParameter(
+                "solana.evm_loader".into(),
+                "<empty>".into(),
+            ));
+        }
+        if Pubkey::from_str(&self.evm_loader).is_err() {
+            return Err(Error::InvalidParameter(
+                "solana.evm_loader".into(),
+                self.evm_loader.clone(),
+            ));
+        }
+        if !self.preflight_commitment.is_empty()
+            && parse_commitment(&self.preflight_commitment).is_none()
+        {
+            return Err(Error::InvalidParameter(
+                "solana.preflight_commitment".into(),
+                self.preflight_commitment.clone(),
+            ));
+        }
+        if !self.confirm_commitment.is_empty()
+            && parse_commitment(&self.confirm_commitment).is_none()
+        {
+            return Err(Error::InvalidParameter(
+                "solana.confirm_commitment".into(),
+                self.confirm_commitment.clone(),
+            ));
+        }
+        if let Some(account) = self
+            .nonce_accounts
+            .iter()
+            .find(|a| Pubkey::from_str(a).is_err())
+        {
+            return Err(Error::InvalidParameter(
+                "solana.nonce_accounts".into(),
+                account.clone(),
+            ));
+        }
+        use nix::NixPath as _; // to check if PathBuf is empty
+        if self.operator_keyfile.is_empty() {
+            return Err(Error::InvalidParameter(
+                "solana.operator_keyfile".into(),
+                "<empty>".into(),
+            ));
         }
         Ok(())
     }
+
+    /// Checks if NEON is transferred through EVM transactions.
+    fn evm_backend(&self) -> bool {
+        self.neon_backend == "evm"
+    }
 }
 
 impl std::fmt::Display for Solana {
@@ -578,6 +1509,60 @@ impl std::fmt::Display for Solana {
         } else {
             writeln!(f)?;
         }
+        write!(
+            f,
+            "solana.preflight_commitment = \"{}\"",
+            self.preflight_commitment
+        )?;
+        if env::var(SOLANA_PREFLIGHT_COMMITMENT).is_ok() {
+            writeln!(f, " (overridden by {})", SOLANA_PREFLIGHT_COMMITMENT)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(
+            f,
+            "solana.confirm_commitment = \"{}\"",
+            self.confirm_commitment
+        )?;
+        if env::var(SOLANA_CONFIRM_COMMITMENT).is_ok() {
+            writeln!(f, " (overridden by {})", SOLANA_CONFIRM_COMMITMENT)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(f, "solana.fire_and_forget = {}", self.fire_and_forget)?;
+        if env::var(SOLANA_FIRE_AND_FORGET).is_ok() {
+            writeln!(f, " (overridden by {})", SOLANA_FIRE_AND_FORGET)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(f, "solana.simulate = {}", self.simulate)?;
+        if env::var(SOLANA_SIMULATE).is_ok() {
+            writeln!(f, " (overridden by {})", SOLANA_SIMULATE)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(f, "solana.durable_nonce = {}", self.durable_nonce)?;
+        if env::var(SOLANA_DURABLE_NONCE).is_ok() {
+            writeln!(f, " (overridden by {})", SOLANA_DURABLE_NONCE)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(f, "solana.nonce_accounts = {:?}", self.nonce_accounts)?;
+        if env::var(SOLANA_NONCE_ACCOUNTS).is_ok() {
+            writeln!(f, " (overridden by {})", SOLANA_NONCE_ACCOUNTS)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(
+            f,
+            "solana.nonce_accounts_count = {}",
+            self.nonce_accounts_count
+        )?;
+        if env::var(SOLANA_NONCE_ACCOUNTS_COUNT).is_ok() {
+            writeln!(f, " (overridden by {})", SOLANA_NONCE_ACCOUNTS_COUNT)?;
+        } else {
+            writeln!(f)?;
+        }
         write!(
             f,
             "solana.evm_loader = {:?}",
@@ -588,6 +1573,12 @@ impl std::fmt::Display for Solana {
         } else {
             writeln!(f)?;
         }
+        write!(f, "solana.neon_backend = \"{}\"", self.neon_backend)?;
+        if env::var(NEON_BACKEND).is_ok() {
+            writeln!(f, " (overridden by {})", NEON_BACKEND)?;
+        } else {
+            writeln!(f)?;
+        }
         write!(f, "solana.operator_keyfile = {:?}", self.operator_keyfile)?;
         if env::var(NEON_OPERATOR_KEYFILE).is_ok() {
             writeln!(f, " (overridden by {})", NEON_OPERATOR_KEYFILE)?;
@@ -608,13 +1599,212 @@ impl std::fmt::Display for Solana {
         }
         write!(f, "solana.per_time_max_amount = {}", self.per_time_max_amount)?;
         if env::var(NEON_ETH_PER_TIME_MAX_AMOUNT).is_ok() {
-            write!(f, " (overridden by {})", NEON_ETH_PER_TIME_MAX_AMOUNT)
+            write!(f, " (overridden by {})", NEON_ETH_PER_TIME_MAX_AMOUNT)?;
         } else {
-            write!(f, "")
+            write!(f, "")?;
+        }
+        for network in self.networks.iter().filter(|n| n.name != DEFAULT_NETWORK) {
+            writeln!(f)?;
+            write!(f, "{}", network)?;
+        }
+        for token in &self.spl_tokens {
+            writeln!(f)?;
+            write!(f, "{}", token)?;
+        }
+        writeln!(f)?;
+        write!(f, "solana.sol_mode = \"{}\"", self.sol_mode)?;
+        if env::var(SOLANA_SOL_MODE).is_ok() {
+            writeln!(f, " (overridden by {})", SOLANA_SOL_MODE)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(f, "solana.sol_max_amount = {}", self.sol_max_amount)?;
+        if env::var(SOLANA_SOL_MAX_AMOUNT).is_ok() {
+            writeln!(f, " (overridden by {})", SOLANA_SOL_MAX_AMOUNT)?;
+        } else {
+            writeln!(f)?;
+        }
+        write!(
+            f,
+            "solana.sol_per_time_max_amount = {}",
+            self.sol_per_time_max_amount
+        )?;
+        if env::var(SOLANA_SOL_PER_TIME_MAX_AMOUNT).is_ok() {
+            write!(f, " (overridden by {})", SOLANA_SOL_PER_TIME_MAX_AMOUNT)?;
+        }
+        Ok(())
+    }
+}
+
+/// Represents a Neon EVM deployment on the Solana cluster.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+struct Network {
+    name: String,
+    evm_loader: String,
+    account_seed_version: u8,           // from neon params
+    token_mint: String,                 // from neon params
+    token_mint_decimals: u8,            // from neon params
+    compute_budget_units: u32,          // from neon params
+    compute_budget_heap_frame: u32,     // from neon params
+    compute_budget_additional_fee: u32, // from neon params
+    max_amount: u64,
+    per_time_max_amount: u64,
+    time_slice_secs: u64,
+    /// Confirmations of EVM transactions of the network; `web3.confirmations` if missing.
+    evm_confirmations: Option<u64>,
+}
+
+impl Network {
+    fn check(&self, check_evm_loader: bool) -> Result<()> {
+        let valid_name = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+        if self.name.is_empty() || !self.name.chars().all(valid_name) {
+            return Err(Error::InvalidParameter(
+                "solana.networks.name".into(),
+                self.name.clone(),
+            ));
+        }
+        if check_evm_loader && Pubkey::from_str(&self.evm_loader).is_err() {
+            return Err(Error::InvalidParameter(
+                format!("solana.networks.{}.evm_loader", self.name),
+                self.evm_loader.clone(),
+            ));
+        }
+        if self.max_amount == 0 {
+            return Err(Error::InvalidParameter(
+                format!("solana.networks.{}.max_amount", self.name),
+                "0".into(),
+            ));
+        }
+        if self.per_time_max_amount == 0 {
+            return Err(Error::InvalidParameter(
+                format!("solana.networks.{}.per_time_max_amount", self.name),
+                "0".into(),
+            ));
+        }
+        if self.time_slice_secs == 0 {
+            return Err(Error::InvalidParameter(
+                format!("solana.networks.{}.time_slice_secs", self.name),
+                "0".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Network {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let prefix = format!("solana.networks.{}", self.name);
+        writeln!(
+            f,
+            "{}.evm_loader = {:?}",
+            prefix,
+            obfuscate_string(&self.evm_loader)
+        )?;
+        writeln!(f, "{}.max_amount = {}", prefix, self.max_amount)?;
+        writeln!(f, "{}.time_slice_secs = {}", prefix, self.time_slice_secs)?;
+        writeln!(
+            f,
+            "{}.per_time_max_amount = {}",
+            prefix, self.per_time_max_amount
+        )?;
+        match self.evm_confirmations {
+            Some(confirmations) => write!(f, "{}.evm_confirmations = {}", prefix, confirmations),
+            None => write!(f, "{}.evm_confirmations = <web3.confirmations>", prefix),
+        }
+    }
+}
+
+/// Represents an SPL token dispensed to Solana wallets.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+struct SplToken {
+    mint: String,
+    symbol: String,
+    max_amount: u64,
+    per_time_max_amount: u64,
+}
+
+impl SplToken {
+    fn check(&self) -> Result<()> {
+        if self.mint != SPL_NEON && Pubkey::from_str(&self.mint).is_err() {
+            return Err(Error::InvalidParameter(
+                "solana.spl_tokens.mint".into(),
+                self.mint.clone(),
+            ));
+        }
+        if self.max_amount == 0 {
+            return Err(Error::InvalidParameter(
+                format!("solana.spl_tokens.{}.max_amount", self.mint),
+                "0".into(),
+            ));
+        }
+        if self.per_time_max_amount == 0 {
+            return Err(Error::InvalidParameter(
+                format!("solana.spl_tokens.{}.per_time_max_amount", self.mint),
+                "0".into(),
+            ));
         }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for SplToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let prefix = format!("solana.spl_tokens.{}", self.mint);
+        writeln!(f, "{}.symbol = {:?}", prefix, self.symbol)?;
+        writeln!(f, "{}.max_amount = {}", prefix, self.max_amount)?;
+        write!(
+            f,
+            "{}.per_time_max_amount = {}",
+            prefix, self.per_time_max_amount
+        )
     }
 }
 
+#[test]
+fn test_init_networks() {
+    let mut solana = Solana {
+        evm_loader: Pubkey::new_unique().to_string(),
+        max_amount: 10,
+        per_time_max_amount: 20,
+        time_slice_secs: 10,
+        token_mint_decimals: 9,
+        networks: vec![Network {
+            name: "second".into(),
+            evm_loader: Pubkey::new_unique().to_string(),
+            max_amount: 1,
+            per_time_max_amount: 2,
+            time_slice_secs: 3,
+            evm_confirmations: Some(4),
+            ..Network::default()
+        }],
+        ..Solana::default()
+    };
+
+    solana.init_networks();
+    solana.init_networks();
+    assert_eq!(solana.networks.len(), 2);
+    assert_eq!(solana.networks[0].name, DEFAULT_NETWORK);
+    assert_eq!(solana.networks[0].evm_loader, solana.evm_loader);
+    assert_eq!(solana.networks[0].token_mint_decimals, 9);
+    assert_eq!(solana.networks[0].evm_confirmations, None);
+    assert_eq!(solana.networks[1].name, "second");
+    assert_eq!(solana.networks[1].evm_confirmations, Some(4));
+    assert!(solana.networks.iter().all(|n| n.check(true).is_ok()));
+
+    solana.networks[1].name = "second/network".into();
+    assert!(solana.networks[1].check(false).is_err());
+    solana.networks[1].name = DEFAULT_NETWORK.into();
+    solana.enable = true;
+    solana.url = "http://localhost:8899".into();
+    solana.commitment = "processed".into();
+    solana.operator_keyfile = "id.json".into();
+    assert!(solana.check().is_err());
+}
+
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 #[serde(default)]
 #[serde(deny_unknown_fields)]
@@ -637,6 +1827,12 @@ impl Faucet {
         self.rpc.check()?;
         self.web3.check()?;
         self.solana.check()?;
+        if self.solana.enable && self.solana.evm_backend() && !self.web3.enable {
+            return Err(Error::InvalidParameter(
+                "web3.enable".into(),
+                "false (required by solana.neon_backend = \"evm\")".into(),
+            ));
+        }
         Ok(())
     }
 }
@@ -718,6 +1914,33 @@ fn parse_list_of_strings(s: &str) -> Result<Vec<String>> {
     s.split(',').map(|s| unquote('"', '"', s)).collect()
 }
 
+/// Parses list of strings like `"key=value"` into a map.
+fn parse_map_of_strings(s: &str) -> Result<HashMap<String, String>> {
+    parse_list_of_strings(s)?
+        .into_iter()
+        .map(|entry| match entry.split_once('=') {
+            Some((alias, address)) if !alias.is_empty() && !address.is_empty() => {
+                Ok((alias.to_string(), address.to_string()))
+            }
+            _ => Err(Error::ParseString(entry)),
+        })
+        .collect()
+}
+
+#[test]
+fn test_parse_map_of_strings() {
+    let aliases = parse_map_of_strings(r#"["USDT=0x00A","WETH=0x00B"]"#).unwrap();
+    assert_eq!(aliases.len(), 2);
+    assert_eq!(aliases["USDT"], "0x00A");
+    assert_eq!(aliases["WETH"], "0x00B");
+
+    let aliases = parse_map_of_strings(r#"["USDT"]"#);
+    assert_eq!(
+        format!("{:?}", aliases.err().unwrap()),
+        "ParseString(\"USDT\")"
+    );
+}
+
 #[test]
 fn test_parse_list_of_strings() {
     let vs = parse_list_of_strings("");
@@ -855,33 +2078,32 @@ fn test_trim_first_and_last_chars() {
     assert_eq!(s, "言处");
 }
 
-/// Reads NEON parameters from the EVM Loader account.
-pub async fn load_neon_params() -> Result<()> {
+/// Reads NEON parameters of the `network` from the EVM Loader account.
+pub async fn load_neon_params(network: &str) -> Result<()> {
+    let evm_loader = solana_evm_loader(network);
     let params = tokio::task::spawn_blocking(move || -> Result<HashMap<String, String>> {
         let client = RpcClient::new_with_commitment(solana_url(), solana_commitment());
-        read_neon_parameters_from_account(client)
+        read_neon_parameters_from_account(client, &evm_loader)
     })
     .await
     .expect("Solana does not respond")?;
 
+    let mut config = CONFIG.write().unwrap();
+    let network = config
+        .solana
+        .networks
+        .iter_mut()
+        .find(|n| n.name == network)
+        .ok_or_else(|| Error::InvalidParameter("network".into(), network.into()))?;
+
     for (param_name, val) in &params {
         match param_name.as_ref() {
-            NEON_SEED_VERSION => {
-                CONFIG.write().unwrap().solana.account_seed_version = val.parse::<u8>()?
-            }
-            NEON_TOKEN_MINT => CONFIG.write().unwrap().solana.token_mint = val.into(),
-            NEON_TOKEN_MINT_DECIMALS => {
-                CONFIG.write().unwrap().solana.token_mint_decimals = val.parse::<u8>()?
-            }
-            NEON_COMPUTE_UNITS => {
-                CONFIG.write().unwrap().solana.compute_budget_units = val.parse::<u32>()?
-            }
-            NEON_HEAP_FRAME => {
-                CONFIG.write().unwrap().solana.compute_budget_heap_frame = val.parse::<u32>()?
-            }
-            NEON_ADDITIONAL_FEE => {
-                CONFIG.write().unwrap().solana.compute_budget_additional_fee = val.parse::<u32>()?
-            }
+            NEON_SEED_VERSION => network.account_seed_version = val.parse::<u8>()?,
+            NEON_TOKEN_MINT => network.token_mint = val.into(),
+            NEON_TOKEN_MINT_DECIMALS => network.token_mint_decimals = val.parse::<u8>()?,
+            NEON_COMPUTE_UNITS => network.compute_budget_units = val.parse::<u32>()?,
+            NEON_HEAP_FRAME => network.compute_budget_heap_frame = val.parse::<u32>()?,
+            NEON_ADDITIONAL_FEE => network.compute_budget_additional_fee = val.parse::<u32>()?,
             _ => {}
         }
     }
@@ -889,9 +2111,12 @@ pub async fn load_neon_params() -> Result<()> {
     Ok(())
 }
 
-fn read_neon_parameters_from_account(client: RpcClient) -> Result<HashMap<String, String>> {
-    let evm_loader_id = Pubkey::from_str(&solana_evm_loader())
-        .map_err(|_| Error::InvalidPubkey(solana_evm_loader()))?;
+fn read_neon_parameters_from_account(
+    client: RpcClient,
+    evm_loader: &str,
+) -> Result<HashMap<String, String>> {
+    let evm_loader_id =
+        Pubkey::from_str(evm_loader).map_err(|_| Error::InvalidPubkey(evm_loader.into()))?;
 
     let account = client.get_account(&evm_loader_id).map_err(|e| {
         error!("Failed get_account: {:?}", e);
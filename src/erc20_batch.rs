@@ -0,0 +1,257 @@
+//! Faucet ERC20 batch transfers module.
+//!
+//! Transfers are sent in one atomic transaction through the `erc20/Disperse.sol`
+//! contract deployed at `web3.batch_contract`. Requests arriving within
+//! `web3.batch_window_ms` are joined into the same transaction.
+//! If the transaction reverts, each transfer is sent on its own.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use eyre::{eyre, Result};
+use tokio::sync::{oneshot, Mutex};
+use tracing::{info, warn};
+
+use secp256k1::SecretKey;
+use web3::contract::Options;
+use web3::signing::{Key as _, SecretKeyRef};
+use web3::transports::Http;
+use web3::types::{Address, H256, U256};
+use web3::Web3;
+
+use crate::web3_client::{self, Abi};
+use crate::{config, erc20_tokens, ethereum, id::ReqId};
+
+/// Represents a single ERC20 transfer of a batch.
+#[derive(Debug, Clone)]
+pub struct Transfer {
+    pub token: Address,
+    pub recipient: Address,
+    pub amount: U256,
+}
+
+/// Result of a transfer: hash of its transaction or the error.
+type Sent = std::result::Result<H256, String>;
+
+/// Represents transfers of a request waiting for the batch to be sent.
+struct Pending {
+    transfers: Vec<Transfer>,
+    reply: oneshot::Sender<Vec<Sent>>,
+}
+
+lazy_static::lazy_static! {
+    static ref PENDING: Mutex<Vec<Pending>> = Mutex::new(Vec::new());
+    /// Held while approving and sending a batch: approvals cover a single batch.
+    static ref SENDING: Mutex<()> = Mutex::new(());
+}
+
+/// Sends `transfers` in one transaction, possibly together with transfers of other requests.
+/// Returns hash of the transaction of each transfer.
+pub async fn transfer(id: &ReqId, transfers: Vec<Transfer>) -> Vec<Result<H256>> {
+    let window = config::web3_batch_window_ms();
+    let results = if window == 0 {
+        send_or_split(id, &transfers).await
+    } else {
+        join(id, transfers, window).await
+    };
+    results
+        .into_iter()
+        .map(|result| result.map_err(|e| eyre!("Batch failed: {}", e)))
+        .collect()
+}
+
+/// Adds the transfers to the pending batch, which is sent after the `window` in milliseconds.
+async fn join(id: &ReqId, transfers: Vec<Transfer>, window: u64) -> Vec<Sent> {
+    let count = transfers.len();
+    let (reply, result) = oneshot::channel();
+    let first = {
+        let mut pending = PENDING.lock().await;
+        pending.push(Pending { transfers, reply });
+        pending.len() == 1
+    };
+    if first {
+        let id = id.to_owned();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(window)).await;
+            flush(&id).await;
+        });
+    } else {
+        info!("{} Joined pending batch", id);
+    }
+
+    result
+        .await
+        .unwrap_or_else(|_| vec![Err("Batch has been dropped".into()); count])
+}
+
+/// Sends all pending transfers and notifies the waiting requests.
+async fn flush(id: &ReqId) {
+    let batch = std::mem::take(&mut *PENDING.lock().await);
+    let transfers = batch
+        .iter()
+        .flat_map(|p| p.transfers.iter().cloned())
+        .collect::<Vec<_>>();
+    info!(
+        "{} Sending batch of {} transfers for {} requests",
+        id,
+        transfers.len(),
+        batch.len()
+    );
+
+    let mut results = send_or_split(id, &transfers).await.into_iter();
+    for pending in batch {
+        let replies = results.by_ref().take(pending.transfers.len()).collect();
+        let _ = pending.reply.send(replies);
+    }
+}
+
+/// Sends the transfers in one transaction; if it reverts, sends each transfer on its own,
+/// so a bad transfer doesn't fail the others.
+async fn send_or_split(id: &ReqId, transfers: &[Transfer]) -> Vec<Sent> {
+    match send(id, transfers).await {
+        Ok(hash) => vec![Ok(hash); transfers.len()],
+        Err(e) if transfers.len() > 1 && e.is::<erc20_tokens::Reverted>() => {
+            warn!(
+                "{} Batch of {} transfers reverted, sending them one by one: {}",
+                id,
+                transfers.len(),
+                e
+            );
+            let mut results = Vec::with_capacity(transfers.len());
+            for t in transfers {
+                results.push(send_alone(id, t).await.map_err(|e| e.to_string()));
+            }
+            results
+        }
+        Err(e) => vec![Err(e.to_string()); transfers.len()],
+    }
+}
+
+/// Sends the transfer directly to the token contract.
+async fn send_alone(id: &ReqId, t: &Transfer) -> Result<H256> {
+    let admin_key: SecretKey = config::web3_private_key().parse()?;
+    let web3 = web3_client::get()?;
+    let token_name = format!("{:?}", t.token);
+    erc20_tokens::transfer(
+        id,
+        &web3,
+        t.token,
+        &token_name,
+        &admin_key,
+        t.recipient,
+        t.amount,
+    )
+    .await
+}
+
+/// Sends the transfers in one transaction of the disperse contract.
+async fn send(id: &ReqId, transfers: &[Transfer]) -> Result<H256> {
+    let admin_key: SecretKey = config::web3_private_key().parse()?;
+    let web3 = web3_client::get()?;
+    let _sending = SENDING.lock().await;
+
+    let disperse_address = ethereum::address_from_str(&config::web3_batch_contract())?;
+    let disperse = web3_client::contract(Abi::Disperse, disperse_address)?;
+
+    approve(id, &web3, &admin_key, disperse_address, transfers).await?;
+
+    let tokens = transfers.iter().map(|t| t.token).collect::<Vec<_>>();
+    let recipients = transfers.iter().map(|t| t.recipient).collect::<Vec<_>>();
+    let values = transfers.iter().map(|t| t.amount).collect::<Vec<_>>();
+    let receipt = erc20_tokens::send_call(
+        id,
+        &web3,
+        &disperse,
+        "disperseTokens",
+        (tokens, recipients, values),
+        &admin_key,
+    )
+    .await?;
+
+    let owner = SecretKeyRef::new(&admin_key).address();
+    let expected = transfers
+        .iter()
+        .map(|t| erc20_tokens::TransferEvent {
+            token: t.token,
+            from: owner,
+            to: t.recipient,
+            amount: t.amount,
+        })
+        .collect::<Vec<_>>();
+    erc20_tokens::verify_transfer_events(&receipt.logs, &expected)?;
+    Ok(receipt.transaction_hash)
+}
+
+/// Approves the disperse contract to spend the batch totals of tokens of the faucet
+/// where the allowance is short.
+async fn approve(
+    id: &ReqId,
+    web3: &Web3<Http>,
+    admin_key: &SecretKey,
+    spender: Address,
+    transfers: &[Transfer],
+) -> Result<()> {
+    let owner = SecretKeyRef::new(admin_key).address();
+    for (token, needed) in totals(transfers)? {
+        let contract = web3_client::contract(Abi::Erc20, token)?;
+        let allowance: U256 = contract
+            .query(
+                "allowance",
+                (owner, spender),
+                None,
+                Options::default(),
+                None,
+            )
+            .await?;
+        if allowance < needed {
+            if !allowance.is_zero() {
+                // Some tokens refuse to change a non-zero allowance
+                erc20_tokens::send_call(
+                    id,
+                    web3,
+                    &contract,
+                    "approve",
+                    (spender, U256::zero()),
+                    admin_key,
+                )
+                .await?;
+            }
+            info!(
+                "{} Approving batch contract to spend {} of token {:?}",
+                id, needed, token
+            );
+            erc20_tokens::send_call(id, web3, &contract, "approve", (spender, needed), admin_key)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Sums transferred amounts per token.
+fn totals(transfers: &[Transfer]) -> Result<BTreeMap<Address, U256>> {
+    let mut totals = BTreeMap::new();
+    for t in transfers {
+        let total = totals.entry(t.token).or_insert_with(U256::zero);
+        *total = total
+            .checked_add(t.amount)
+            .ok_or_else(|| eyre!("Overflow {} + {}", total, t.amount))?;
+    }
+    Ok(totals)
+}
+
+#[test]
+fn test_totals() {
+    let a = Address::from_low_u64_be(1);
+    let b = Address::from_low_u64_be(2);
+    let recipient = Address::from_low_u64_be(3);
+    let transfer = |token, amount: u64| Transfer {
+        token,
+        recipient,
+        amount: amount.into(),
+    };
+
+    let totals = totals(&[transfer(a, 1), transfer(b, 2), transfer(a, 3)]).unwrap();
+    assert_eq!(totals.len(), 2);
+    assert_eq!(totals[&a], U256::from(4));
+    assert_eq!(totals[&b], U256::from(2));
+}
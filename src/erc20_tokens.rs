@@ -1,163 +1,1158 @@
 //! Faucet ERC20 tokens module.
 
+use std::time::{Duration, Instant};
+
 use eyre::{eyre, Result};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use secp256k1::SecretKey;
 use web3::api::Eth;
+use web3::contract::tokens::Tokenize;
 use web3::contract::{Contract, Options};
-use web3::signing::Key;
-use web3::types::U256;
-use web3::Transport;
+use web3::signing::{keccak256, Key as _, SecretKeyRef};
+use web3::transports::Http;
+use web3::types::{
+    BlockNumber, FeeHistory, Log, TransactionParameters, TransactionReceipt, H256, U256, U64,
+};
+use web3::{Transport, Web3};
 
-use crate::{config, ethereum, id::ReqId};
+use crate::web3_client::{self, Abi};
+use crate::{api, config, erc20_batch, eth_nonce, ethereum, id::ReqId};
 
 /// Represents packet of information needed for an airdrop operation.
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct Airdrop {
     /// Ethereum address of the recipient.
-    wallet: String,
+    pub wallet: String,
     /// ERC20 Token address. If missing, do all tokens.
-    token_addr: Option<String>,
-    /// Amount of a token to be received.
-    amount: u64,
+    pub token_addr: Option<String>,
+    /// ERC20 Token symbol, alias or address. Takes precedence over `token_addr`.
+    pub token: Option<String>,
+    /// Amount of a token to be received: whole tokens or a decimal string like "0.25".
+    pub amount: Amount,
+    /// Flag to interpret the amount in base units (fractions) of a token.
+    #[serde(default)]
+    pub in_base_units: bool,
 }
 
-/// Processes the airdrop: sends needed transactions into Ethereum.
-pub async fn airdrop(id: &ReqId, params: Airdrop) -> Result<()> {
-    info!("{} Processing ERC20 {:?}...", id, params);
+/// Represents a requested amount of tokens.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+#[serde(untagged)]
+pub enum Amount {
+    Number(u64),
+    Text(String),
+}
 
-    let mut known_tokens = config::tokens();
+impl api::Validate for Airdrop {
+    fn validate(&self) -> Result<(), String> {
+        ethereum::address_from_str(&self.wallet)
+            .map_err(|e| format!("Invalid wallet '{}': {}", self.wallet, e))?;
+        api::Validate::validate(&self.amount)
+    }
+}
 
-    if let Some(ref token_addr) = params.token_addr {
-        if !known_tokens.contains(token_addr) {
-            return Err(eyre!("Requested unknown ERC20 '{}'", token_addr));
+impl api::Validate for Amount {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Amount::Number(0) => Err("Amount must be positive".into()),
+            Amount::Text(text) if text.trim().is_empty() => Err("Amount is empty".into()),
+            _ => Ok(()),
         }
     }
+}
+
+/// Represents an error of an amount which can't be converted into base units.
+#[derive(thiserror::Error, Debug)]
+#[error("Invalid amount '{amount}': {reason}")]
+pub struct InvalidAmount {
+    amount: String,
+    reason: String,
+}
+
+/// Represents the result of the airdrop of a single token.
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct TokenResult {
+    pub token: String,
+    /// Hash of the transaction which delivered the token.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
 
-    if params.amount > config::web3_max_amount() {
-        return Err(eyre!(
-            "Requested value {} exceeds the limit {}",
-            params.amount,
-            config::web3_max_amount()
-        ));
+impl TokenResult {
+    pub fn new(token: String, result: &Result<H256>) -> Self {
+        match result {
+            Ok(hash) => Self {
+                token,
+                tx: Some(format!("{:?}", hash)),
+                error: None,
+            },
+            Err(e) => Self {
+                token,
+                tx: None,
+                error: Some(e.to_string()),
+            },
+        }
     }
+}
 
-    let admin_key: SecretKey = config::web3_private_key().parse()?;
-    let http = web3::transports::Http::new(&config::web3_rpc_url())?;
-    let web3 = web3::Web3::new(http);
+/// Represents the overall outcome of an airdrop of several tokens.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Success,
+    PartialSuccess,
+    Failure,
+}
 
-    if TOKENS.read().await.is_empty() {
-        init(id, web3.eth().clone(), &known_tokens).await?;
+/// Classifies results of the airdrop.
+pub fn outcome<'a>(results: impl IntoIterator<Item = &'a TokenResult>) -> Outcome {
+    let (mut total, mut failed) = (0, 0);
+    for result in results {
+        total += 1;
+        if result.error.is_some() {
+            failed += 1;
+        }
+    }
+    if failed == 0 {
+        Outcome::Success
+    } else if failed < total {
+        Outcome::PartialSuccess
+    } else {
+        Outcome::Failure
     }
+}
+
+#[test]
+fn test_outcome() {
+    let ok = || TokenResult::new("a".into(), &Ok(H256::zero()));
+    let failed = || TokenResult::new("b".into(), &Err(eyre!("failed")));
+
+    assert_eq!(outcome(&[ok(), ok()]), Outcome::Success);
+    assert_eq!(outcome(&[ok(), failed()]), Outcome::PartialSuccess);
+    assert_eq!(outcome(&[failed(), failed()]), Outcome::Failure);
+}
+
+/// Represents a checked airdrop whose amounts are accounted in the per time limit.
+#[derive(Debug)]
+pub struct Prepared {
+    recipient: ethereum::Address,
+    /// Token address, amount in base units and decimals of each token.
+    amounts: Vec<(String, U256, u32)>,
+}
+
+/// Validates the airdrop and checks its amounts against the caps;
+/// validation errors fail the whole request before anything is sent.
+/// The amounts are accounted in the per time limit until sent or cancelled.
+pub async fn prepare(id: &ReqId, params: &Airdrop) -> Result<Prepared> {
+    info!("{} Processing ERC20 {:?}...", id, params);
+
+    let mut known_tokens = config::tokens();
+
+    ensure(id, &known_tokens).await?;
 
     let recipient = ethereum::address_from_str(&params.wallet)?;
-    let amount = U256::from(params.amount);
 
-    if let Some(token_addr) = params.token_addr {
-        known_tokens = vec![token_addr]
+    if let Some(token) = params.token.as_ref().or(params.token_addr.as_ref()) {
+        known_tokens = vec![resolve(token, &known_tokens).await?]
+    } else {
+        let tokens = TOKENS.read().await;
+        known_tokens.retain(|token| tokens.contains_key(token));
+        if known_tokens.is_empty() {
+            return Err(eyre!("No ERC20 tokens are available"));
+        }
     };
 
+    let mut amounts = Vec::with_capacity(known_tokens.len());
     for token in &known_tokens {
-        let factor = U256::from(multiplication_factor(token).await?);
-        let internal_amount = amount
-            .checked_mul(factor)
-            .ok_or_else(|| eyre!("Overflow {} * {}", amount, factor))?;
-        transfer(
-            id,
-            web3.eth(),
-            ethereum::address_from_str(token)?,
-            token,
-            &admin_key,
-            recipient,
-            internal_amount,
-        )
-        .await
-        .map_err(|e| {
-            error!("{} Failed transfer of token {}: {}", id, token, e);
-            e
-        })?;
+        let decimals = decimals(token).await?;
+        let amount = parse_amount(&params.amount, decimals, params.in_base_units)?;
+        let limit = whole_to_base_units(config::web3_max_amount(), decimals)?;
+        if amount > limit {
+            return Err(eyre!(
+                "Requested value {} of token {} exceeds the limit {}",
+                amount,
+                token,
+                limit
+            ));
+        }
+        amounts.push((token.clone(), amount, decimals));
     }
 
-    Ok(())
+    check_per_time_cap(recipient, &amounts).await?;
+
+    Ok(Prepared { recipient, amounts })
+}
+
+/// Releases the amounts of an airdrop which won't be sent.
+pub async fn cancel(prepared: Prepared) {
+    let amounts = prepared
+        .amounts
+        .into_iter()
+        .map(|(token, amount, _)| (token, amount))
+        .collect::<Vec<_>>();
+    release_per_time_cap(prepared.recipient, &amounts).await;
+}
+
+/// Processes the airdrop: sends needed transactions into Ethereum.
+/// Every token is tried even if others fail; returns the result per token.
+/// Amounts of failed tokens are released from the per time limit.
+pub async fn send(id: &ReqId, prepared: Prepared) -> Vec<TokenResult> {
+    let Prepared { recipient, amounts } = prepared;
+
+    let signer = match config::web3_private_key().parse::<SecretKey>() {
+        Ok(key) => web3_client::get().map(|web3| (key, web3)),
+        Err(e) => Err(e.into()),
+    };
+    let (admin_key, web3) = match signer {
+        Ok(signer) => signer,
+        Err(e) => {
+            error!("{} Failed preparing transactions: {}", id, e);
+            let failed = amounts
+                .iter()
+                .map(|(token, amount, _)| (token.clone(), *amount))
+                .collect::<Vec<_>>();
+            release_per_time_cap(recipient, &failed).await;
+            let error = e.to_string();
+            return amounts
+                .into_iter()
+                .map(|(token, _, _)| TokenResult {
+                    token,
+                    tx: None,
+                    error: Some(error.clone()),
+                })
+                .collect();
+        }
+    };
+
+    let mut results = Vec::with_capacity(amounts.len());
+    let mut failed = Vec::new();
+    let mut transferred = Vec::with_capacity(amounts.len());
+    for (token, amount, decimals) in amounts {
+        if !is_minted(&token).await {
+            transferred.push((token, amount, decimals));
+            continue;
+        }
+        let result = match ethereum::address_from_str(&token) {
+            Ok(address) => mint(id, &web3, address, &token, &admin_key, recipient, amount).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = &result {
+            error!("{} Failed mint of token {}: {}", id, token, e);
+            failed.push((token.clone(), amount));
+        }
+        results.push(TokenResult::new(token, &result));
+    }
+    let amounts = transferred;
+
+    if config::web3_batch_enabled() && !amounts.is_empty() {
+        let transfers = amounts
+            .iter()
+            .map(|(token, amount, _)| {
+                Ok(erc20_batch::Transfer {
+                    token: ethereum::address_from_str(token)?,
+                    recipient,
+                    amount: *amount,
+                })
+            })
+            .collect::<Result<Vec<_>>>();
+        let sent = match transfers {
+            Ok(transfers) => erc20_batch::transfer(id, transfers).await,
+            Err(e) => {
+                let e = e.to_string();
+                amounts.iter().map(|_| Err(eyre!("{}", e))).collect()
+            }
+        };
+        for ((token, amount, _), result) in amounts.into_iter().zip(sent) {
+            if let Err(e) = &result {
+                error!("{} Failed batch transfer of token {}: {}", id, token, e);
+                failed.push((token.clone(), amount));
+            }
+            results.push(TokenResult::new(token, &result));
+        }
+    } else {
+        for (token, amount, _) in amounts {
+            let result = match ethereum::address_from_str(&token) {
+                Ok(address) => {
+                    transfer(id, &web3, address, &token, &admin_key, recipient, amount).await
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = &result {
+                error!("{} Failed transfer of token {}: {}", id, token, e);
+                failed.push((token.clone(), amount));
+            }
+            results.push(TokenResult::new(token, &result));
+        }
+    }
+
+    release_per_time_cap(recipient, &failed).await;
+    results
+}
+
+/// Reads properties of configured tokens from the chain into the local cache.
+pub async fn load(id: &ReqId) -> Result<()> {
+    init(id, &config::tokens()).await
+}
+
+/// Re-validates all configured tokens and forgets tokens removed from the configuration.
+pub async fn refresh(id: &ReqId) -> Result<()> {
+    let addresses = config::tokens();
+    TOKENS
+        .write()
+        .await
+        .retain(|address, _| addresses.contains(address));
+    UNAVAILABLE
+        .write()
+        .await
+        .retain(|address, _| addresses.contains(address));
+    init(id, &addresses).await
+}
+
+/// Checks tokens missing in the local cache, including unavailable ones
+/// whose last check is older than `RECHECK_INTERVAL`.
+async fn ensure(id: &ReqId, addresses: &[String]) -> Result<()> {
+    let missing = {
+        let tokens = TOKENS.read().await;
+        let unavailable = UNAVAILABLE.read().await;
+        let now = Instant::now();
+        addresses
+            .iter()
+            .filter(|address| needs_check(&tokens, &unavailable, address, now))
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    if missing.is_empty() {
+        return Ok(());
+    }
+    init(id, &missing).await
 }
 
-/// Initializes local cache of tokens properties.
-async fn init<T: Transport>(id: &ReqId, eth: Eth<T>, addresses: &[String]) -> Result<()> {
+/// Checks tokens and updates the local cache of their properties.
+/// Tokens which are definitely not ERC20 contracts are marked unavailable;
+/// a token failing the check for another reason (e.g. a transport error)
+/// keeps its last good properties if it has any.
+async fn init(id: &ReqId, addresses: &[String]) -> Result<()> {
     info!("{} Checking tokens...", id);
 
+    let admin_key: SecretKey = config::web3_private_key().parse()?;
+    let owner = SecretKeyRef::new(&admin_key).address();
+
+    let mut failed = 0;
     for token_address in addresses {
-        let a = ethereum::address_from_str(token_address)?;
-        TOKENS.write().await.insert(
-            token_address.to_string(),
-            Token::new(get_decimals(id, eth.clone(), a).await?),
+        let result = check(id, token_address, owner).await;
+        let kept = update_cache(
+            &mut *TOKENS.write().await,
+            &mut *UNAVAILABLE.write().await,
+            token_address,
+            &result,
+            Instant::now(),
         );
+        match result {
+            Ok(_) => (),
+            Err(e) if kept => warn!("{} Failed checking token {}: {}", id, token_address, e),
+            Err(e) => {
+                error!("{} Token {} is unavailable: {}", id, token_address, e);
+                failed += 1;
+            }
+        }
     }
 
-    info!("{} All tokens are deployed and sane", id);
+    if failed == 0 {
+        info!("{} All tokens are deployed and sane", id);
+    } else {
+        warn!(
+            "{} {} of {} tokens are unavailable",
+            id,
+            failed,
+            addresses.len()
+        );
+    }
     Ok(())
 }
 
-/// Creates and sends a transfer transaction.
-async fn transfer<T: Transport>(
+/// Checks if the token is missing in the cache and wasn't found unavailable
+/// less than `RECHECK_INTERVAL` before `now`.
+fn needs_check(
+    tokens: &Tokens,
+    unavailable: &std::collections::HashMap<String, Unavailable>,
+    token_address: &str,
+    now: Instant,
+) -> bool {
+    !tokens.contains_key(token_address)
+        && unavailable
+            .get(token_address)
+            .map_or(true, |u| now.duration_since(u.checked) >= RECHECK_INTERVAL)
+}
+
+/// Updates the cache with the result of the token check made at `now`.
+/// Returns `true` if the failed token keeps its last good properties.
+fn update_cache(
+    tokens: &mut Tokens,
+    unavailable: &mut std::collections::HashMap<String, Unavailable>,
+    token_address: &str,
+    result: &Result<Token>,
+    now: Instant,
+) -> bool {
+    match result {
+        Ok(token) => {
+            unavailable.remove(token_address);
+            tokens.insert(token_address.to_string(), token.clone());
+            false
+        }
+        Err(e) if e.downcast_ref::<NotToken>().is_none() && tokens.contains_key(token_address) => {
+            true
+        }
+        Err(e) => {
+            tokens.remove(token_address);
+            unavailable.insert(
+                token_address.to_string(),
+                Unavailable {
+                    reason: e.to_string(),
+                    checked: now,
+                },
+            );
+            false
+        }
+    }
+}
+
+#[test]
+fn test_update_cache() {
+    let mut tokens = Tokens::default();
+    let mut unavailable = std::collections::HashMap::new();
+    let token = Token::new(6, "USDT".into(), "Tether".into(), false);
+    let address = "0x00000000000000000000000000000000CafeBabe";
+    let now = Instant::now();
+
+    // A token failing the first check is unavailable until the recheck interval passes
+    let failed = Err(eyre!("connection refused"));
+    assert!(!update_cache(
+        &mut tokens,
+        &mut unavailable,
+        address,
+        &failed,
+        now
+    ));
+    assert!(!tokens.contains_key(address));
+    assert_eq!(unavailable[address].reason, "connection refused");
+    assert!(!needs_check(&tokens, &unavailable, address, now));
+    let later = now + RECHECK_INTERVAL;
+    assert!(needs_check(&tokens, &unavailable, address, later));
+
+    let checked = Ok(token.clone());
+    assert!(!update_cache(
+        &mut tokens,
+        &mut unavailable,
+        address,
+        &checked,
+        later
+    ));
+    assert_eq!(tokens[address].symbol, "USDT");
+    assert!(!unavailable.contains_key(address));
+    assert!(!needs_check(&tokens, &unavailable, address, later));
+
+    // A transient failure keeps the last good properties
+    assert!(update_cache(
+        &mut tokens,
+        &mut unavailable,
+        address,
+        &failed,
+        later
+    ));
+    assert_eq!(tokens[address].decimals, 6);
+    assert!(!unavailable.contains_key(address));
+
+    // A definitive failure drops the token
+    let not_token = Err(NotToken("No contract".into()).into());
+    assert!(!update_cache(
+        &mut tokens,
+        &mut unavailable,
+        address,
+        &not_token,
+        later
+    ));
+    assert!(!tokens.contains_key(address));
+    assert_eq!(unavailable[address].reason, "No contract");
+    assert!(!needs_check(&tokens, &unavailable, address, later));
+}
+
+/// Represents a definitive failure of a token check: the address is not an ERC20 contract.
+#[derive(thiserror::Error, Debug)]
+#[error("{0}")]
+struct NotToken(String);
+
+/// Reads properties of the token from the chain.
+async fn check(id: &ReqId, token_address: &str, owner: ethereum::Address) -> Result<Token> {
+    let a = ethereum::address_from_str(token_address)?;
+    let code = web3_client::get()?.eth().code(a, None).await?;
+    if code.0.is_empty() {
+        return Err(NotToken(format!("No contract at {}", token_address)).into());
+    }
+    let decimals = get_decimals(id, a).await?;
+    let (symbol, name) = get_metadata(id, a).await;
+    let mintable =
+        config::web3_token_mode(token_address) == config::AUTO && can_mint(id, a, owner).await;
+    Ok(Token::new(decimals, symbol, name, mintable))
+}
+
+/// Represents an error of a token which can't be resolved.
+#[derive(thiserror::Error, Debug)]
+#[error("{reason} '{token}'; valid options: {}", options.join(", "))]
+pub struct InvalidToken {
+    reason: &'static str,
+    token: String,
+    options: Vec<String>,
+}
+
+/// Resolves the token given by symbol, alias or address into one of the `known` addresses.
+async fn resolve(token: &str, known: &[String]) -> Result<String> {
+    let tokens = TOKENS.read().await;
+    let invalid = |reason| InvalidToken {
+        reason,
+        token: token.to_string(),
+        options: known
+            .iter()
+            .map(|address| match tokens.get(address) {
+                Some(t) if !t.symbol.is_empty() => format!("{} ({})", t.symbol, address),
+                _ => address.clone(),
+            })
+            .collect(),
+    };
+
+    let find = |address: ethereum::Address| {
+        known
+            .iter()
+            .find(|k| ethereum::address_from_str(k).ok() == Some(address))
+            .cloned()
+    };
+
+    if let Ok(address) = ethereum::address_from_str(token) {
+        ethereum::address_from_str_checked(token)
+            .map_err(|_| invalid("Invalid checksum of token address"))?;
+        return find(address).ok_or_else(|| invalid("Unknown token").into());
+    }
+
+    let aliases = config::web3_token_aliases();
+    if let Some((_, address)) = aliases
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(token))
+    {
+        let address = ethereum::address_from_str(address)?;
+        return find(address).ok_or_else(|| invalid("Unknown token").into());
+    }
+
+    let matches = known
+        .iter()
+        .filter(|address| {
+            tokens
+                .get(*address)
+                .map_or(false, |t| t.symbol.eq_ignore_ascii_case(token))
+        })
+        .collect::<Vec<_>>();
+    match matches.as_slice() {
+        [address] => Ok((*address).clone()),
+        [] => Err(invalid("Unknown token").into()),
+        _ => Err(invalid("Ambiguous token symbol").into()),
+    }
+}
+
+/// Represents an entry of the list of available tokens.
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct TokenInfo {
+    pub address: String,
+    /// Flag that the token passed the check and can be requested.
+    pub available: bool,
+    /// Reason of unavailability of the token.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub symbol: String,
+    pub name: String,
+    pub decimals: u32,
+    /// Largest amount of whole tokens per request.
+    pub max_amount: u64,
+    /// Largest amount of whole tokens per wallet per time slice; 0 means no cap.
+    pub per_time_max_amount: u64,
+    /// Remaining balance of the faucet in token fractions.
+    pub balance: String,
+}
+
+impl TokenInfo {
+    fn available(address: String, token: Token, balance: U256) -> Self {
+        Self {
+            address,
+            available: true,
+            reason: None,
+            symbol: token.symbol,
+            name: token.name,
+            decimals: token.decimals,
+            max_amount: config::web3_max_amount(),
+            per_time_max_amount: config::web3_per_time_max_amount(),
+            balance: balance.to_string(),
+        }
+    }
+
+    fn unavailable(address: String, reason: String) -> Self {
+        Self {
+            address,
+            available: false,
+            reason: Some(reason),
+            symbol: String::default(),
+            name: String::default(),
+            decimals: 0,
+            max_amount: config::web3_max_amount(),
+            per_time_max_amount: config::web3_per_time_max_amount(),
+            balance: String::default(),
+        }
+    }
+}
+
+/// Returns available tokens with their metadata, caps and balances of the faucet.
+pub async fn list(id: &ReqId) -> Result<Vec<TokenInfo>> {
+    let tokens = config::tokens();
+    ensure(id, &tokens).await?;
+
+    let admin_key: SecretKey = config::web3_private_key().parse()?;
+    let owner = SecretKeyRef::new(&admin_key).address();
+
+    let mut list = Vec::with_capacity(tokens.len());
+    for address in tokens {
+        let token = TOKENS.read().await.get(&address).cloned();
+        let token = match token {
+            Some(token) => token,
+            None => {
+                let reason = unavailable_reason(&address).await;
+                list.push(TokenInfo::unavailable(address, reason));
+                continue;
+            }
+        };
+        match get_balance(&address, owner).await {
+            Ok(balance) => list.push(TokenInfo::available(address, token, balance)),
+            Err(e) => {
+                warn!("{} Failed reading balance of token {}: {}", id, address, e);
+                list.push(TokenInfo::unavailable(address, e.to_string()));
+            }
+        }
+    }
+
+    Ok(list)
+}
+
+#[test]
+fn test_token_info() {
+    let token = Token {
+        decimals: 6,
+        symbol: "USDT".into(),
+        name: "Tether".into(),
+        mintable: false,
+    };
+    let info = TokenInfo::available("0x1234".into(), token, U256::from(1_500_000));
+    assert_eq!(
+        serde_json::to_value(&info).unwrap(),
+        serde_json::json!({
+            "address": "0x1234",
+            "available": true,
+            "symbol": "USDT",
+            "name": "Tether",
+            "decimals": 6,
+            "max_amount": config::web3_max_amount(),
+            "per_time_max_amount": config::web3_per_time_max_amount(),
+            "balance": "1500000",
+        })
+    );
+
+    let info = TokenInfo::unavailable("0x5678".into(), "Not a contract".into());
+    assert_eq!(
+        serde_json::to_value(&info).unwrap(),
+        serde_json::json!({
+            "address": "0x5678",
+            "available": false,
+            "reason": "Not a contract",
+            "symbol": "",
+            "name": "",
+            "decimals": 0,
+            "max_amount": config::web3_max_amount(),
+            "per_time_max_amount": config::web3_per_time_max_amount(),
+            "balance": "",
+        })
+    );
+}
+
+/// Reads balance of the `owner` in the token.
+async fn get_balance(token_address: &str, owner: ethereum::Address) -> Result<U256> {
+    let contract = web3_client::contract(Abi::Erc20, ethereum::address_from_str(token_address)?)?;
+    Ok(contract
+        .query("balanceOf", owner, None, Options::default(), None)
+        .await?)
+}
+
+/// Returns the reason why the token is missing in the local cache.
+async fn unavailable_reason(token_address: &str) -> String {
+    UNAVAILABLE
+        .read()
+        .await
+        .get(token_address)
+        .map_or_else(|| "Not checked yet".to_string(), |u| u.reason.clone())
+}
+
+/// Represents an error of a request exceeding `web3.per_time_max_amount`.
+#[derive(thiserror::Error, Debug)]
+#[error("Requested total {total} of token {token} exceeds the per time limit {cap}")]
+pub struct PerTimeCapExceeded {
+    token: String,
+    total: U256,
+    cap: U256,
+}
+
+/// Checks and accounts the amounts of tokens in base units requested by the `recipient`
+/// against `web3.per_time_max_amount`.
+/// Each entry of `amounts` holds token address, amount and decimals of the token.
+async fn check_per_time_cap(
+    recipient: ethereum::Address,
+    amounts: &[(String, U256, u32)],
+) -> Result<()> {
+    let whole_cap = config::web3_per_time_max_amount();
+    if whole_cap == 0 {
+        return Ok(());
+    }
+
+    let mut distributed = DISTRIBUTED.write().await;
+    let time_slice = Duration::from_secs(config::web3_time_slice_secs());
+    if distributed.since.elapsed() >= time_slice {
+        *distributed = Distributed::default();
+    }
+
+    for (token, amount, decimals) in amounts {
+        let cap = whole_to_base_units(whole_cap, *decimals)?;
+        let total = distributed
+            .amounts
+            .get(&(token.clone(), recipient))
+            .copied()
+            .unwrap_or_default()
+            .saturating_add(*amount);
+        if total > cap {
+            return Err(PerTimeCapExceeded {
+                token: token.clone(),
+                total,
+                cap,
+            }
+            .into());
+        }
+    }
+    for (token, amount, _) in amounts {
+        let total = distributed
+            .amounts
+            .entry((token.clone(), recipient))
+            .or_default();
+        *total = total.saturating_add(*amount);
+    }
+
+    Ok(())
+}
+
+/// Returns amounts of tokens which have not been sent back to the per time limit.
+async fn release_per_time_cap(recipient: ethereum::Address, amounts: &[(String, U256)]) {
+    let mut distributed = DISTRIBUTED.write().await;
+    for (token, amount) in amounts {
+        if let Some(total) = distributed.amounts.get_mut(&(token.clone(), recipient)) {
+            *total = total.saturating_sub(*amount);
+        }
+    }
+}
+
+/// Creates and sends a transfer transaction, returns its hash.
+pub async fn transfer(
     id: &ReqId,
-    eth: Eth<T>,
+    web3: &Web3<Http>,
     token: ethereum::Address,
     token_name: &str,
-    admin_key: impl Key + std::fmt::Debug,
+    admin_key: &SecretKey,
     recipient: ethereum::Address,
     amount: U256,
-) -> web3::contract::Result<()> {
+) -> Result<H256> {
     info!(
         "{} Transfer {} of token {} -> {}",
         id, amount, token_name, recipient
     );
-    let token =
-        Contract::from_json(eth, token, include_bytes!("../erc20/ERC20.abi")).map_err(|e| {
-            error!("{} Failed reading ERC20.abi: {}", id, e);
-            e
-        })?;
+    let token = web3_client::contract(Abi::Erc20, token)?;
 
-    debug!(
-        "{} Sending transaction for transfer of token {}...",
-        id, token_name
+    let receipt = send_call(id, web3, &token, "transfer", (recipient, amount), admin_key).await?;
+    let owner = SecretKeyRef::new(admin_key).address();
+    let expected = expected_event(token.address(), owner, recipient, amount, false);
+    verify_transfer_events(&receipt.logs, &[expected])?;
+    Ok(receipt.transaction_hash)
+}
+
+/// Creates and sends a mint transaction, returns its hash.
+async fn mint(
+    id: &ReqId,
+    web3: &Web3<Http>,
+    token: ethereum::Address,
+    token_name: &str,
+    admin_key: &SecretKey,
+    recipient: ethereum::Address,
+    amount: U256,
+) -> Result<H256> {
+    info!(
+        "{} Mint {} of token {} -> {}",
+        id, amount, token_name, recipient
     );
-    let options = web3::contract::Options {
-        gas: Some(U256::from(10_000_000)),
-        ..Default::default()
+    let token = web3_client::contract(Abi::Erc20Mintable, token)?;
+
+    let receipt = send_call(id, web3, &token, "mint", (recipient, amount), admin_key).await?;
+    let owner = SecretKeyRef::new(admin_key).address();
+    let expected = expected_event(token.address(), owner, recipient, amount, true);
+    verify_transfer_events(&receipt.logs, &[expected])?;
+    Ok(receipt.transaction_hash)
+}
+
+/// Represents an expected ERC20 `Transfer` event.
+#[derive(Debug)]
+pub struct TransferEvent {
+    pub token: ethereum::Address,
+    pub from: ethereum::Address,
+    pub to: ethereum::Address,
+    pub amount: U256,
+}
+
+impl TransferEvent {
+    /// Checks if the log is this event.
+    fn matches(&self, log: &Log) -> bool {
+        let topic = H256::from(keccak256(b"Transfer(address,address,uint256)"));
+        log.address == self.token
+            && log.topics.len() == 3
+            && log.topics[0] == topic
+            && log.topics[1] == H256::from(self.from)
+            && log.topics[2] == H256::from(self.to)
+            && log.data.0.len() == 32
+            && U256::from_big_endian(&log.data.0) == self.amount
+    }
+}
+
+/// Returns the `Transfer` event of tokens sent by the `owner` or, if `minted`, created by a mint.
+fn expected_event(
+    token: ethereum::Address,
+    owner: ethereum::Address,
+    recipient: ethereum::Address,
+    amount: U256,
+    minted: bool,
+) -> TransferEvent {
+    TransferEvent {
+        token,
+        from: if minted {
+            ethereum::Address::zero()
+        } else {
+            owner
+        },
+        to: recipient,
+        amount,
+    }
+}
+
+/// Checks that each expected event has its own log in the transaction receipt.
+pub fn verify_transfer_events(logs: &[Log], expected: &[TransferEvent]) -> Result<()> {
+    let mut unmatched = logs.iter().collect::<Vec<_>>();
+    for event in expected {
+        match unmatched.iter().position(|log| event.matches(log)) {
+            Some(i) => {
+                unmatched.swap_remove(i);
+            }
+            None => return Err(eyre!("Transfer event not found: {:?}", event)),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_verify_transfer_events() {
+    let token = ethereum::Address::from_low_u64_be(1);
+    let from = ethereum::Address::from_low_u64_be(2);
+    let to = ethereum::Address::from_low_u64_be(3);
+    let log_from = |from: ethereum::Address, amount: u64| {
+        let mut data = [0_u8; 32];
+        U256::from(amount).to_big_endian(&mut data);
+        Log {
+            address: token,
+            topics: vec![
+                H256::from(keccak256(b"Transfer(address,address,uint256)")),
+                H256::from(from),
+                H256::from(to),
+            ],
+            data: data.to_vec().into(),
+            block_hash: None,
+            block_number: None,
+            transaction_hash: None,
+            transaction_index: None,
+            log_index: None,
+            transaction_log_index: None,
+            log_type: None,
+            removed: None,
+        }
+    };
+    let log = |amount: u64| log_from(from, amount);
+    let event = |amount: u64| TransferEvent {
+        token,
+        from,
+        to,
+        amount: amount.into(),
+    };
+
+    assert!(verify_transfer_events(&[log(5)], &[event(5)]).is_ok());
+    assert!(verify_transfer_events(&[log(5), log(7)], &[event(7), event(5)]).is_ok());
+    assert!(verify_transfer_events(&[log(5)], &[event(6)]).is_err());
+    assert!(verify_transfer_events(&[log(5)], &[event(5), event(5)]).is_err());
+    assert!(verify_transfer_events(&[], &[event(5)]).is_err());
+
+    // A mint emits the event from the zero address
+    let minted = log_from(ethereum::Address::zero(), 5);
+    let expected = |minted| expected_event(token, from, to, U256::from(5), minted);
+    assert!(verify_transfer_events(&[minted.clone()], &[expected(true)]).is_ok());
+    assert!(verify_transfer_events(&[minted], &[expected(false)]).is_err());
+    assert!(verify_transfer_events(&[log(5)], &[expected(false)]).is_ok());
+    assert!(verify_transfer_events(&[log(5)], &[expected(true)]).is_err());
+}
+
+/// Checks if the token is distributed by minting.
+async fn is_minted(token: &str) -> bool {
+    let mode = config::web3_token_mode(token);
+    is_minted_in_mode(&mode, TOKENS.read().await.get(token))
+}
+
+/// Checks if the token is distributed by minting in the `mode`;
+/// in the auto mode, only a checked token allowing the faucet to mint is minted.
+fn is_minted_in_mode(mode: &str, token: Option<&Token>) -> bool {
+    match mode {
+        config::TOKEN_MODE_MINT => true,
+        config::AUTO => token.map_or(false, |t| t.mintable),
+        _ => false,
+    }
+}
+
+#[test]
+fn test_is_minted_in_mode() {
+    let mintable = Token::new(18, "T".into(), "Token".into(), true);
+    let owned = Token::new(18, "T".into(), "Token".into(), false);
+
+    assert!(is_minted_in_mode(config::TOKEN_MODE_MINT, None));
+    assert!(is_minted_in_mode(config::TOKEN_MODE_MINT, Some(&owned)));
+    assert!(!is_minted_in_mode(config::TOKEN_MODE_TRANSFER, None));
+    assert!(!is_minted_in_mode(
+        config::TOKEN_MODE_TRANSFER,
+        Some(&mintable)
+    ));
+    assert!(is_minted_in_mode(config::AUTO, Some(&mintable)));
+    assert!(!is_minted_in_mode(config::AUTO, Some(&owned)));
+    assert!(!is_minted_in_mode(config::AUTO, None));
+}
+
+/// Probes whether the `owner` is allowed to call `mint` of the token.
+async fn can_mint(id: &ReqId, token_address: ethereum::Address, owner: ethereum::Address) -> bool {
+    let token = match web3_client::contract(Abi::Erc20Mintable, token_address) {
+        Ok(token) => token,
+        Err(e) => {
+            error!("{} {}", id, e);
+            return false;
+        }
     };
-    token
-        .signed_call_with_confirmations(
-            "transfer",
-            (recipient, amount),
-            options,
-            0, // confirmations
-            admin_key,
+
+    let probe = token
+        .estimate_gas("mint", (owner, U256::one()), owner, Options::default())
+        .await;
+    debug!(
+        "{} ERC20 token {} mint probe: {:?}",
+        id, token_address, probe
+    );
+    probe.is_ok()
+}
+
+/// Represents a failed call which has changed nothing:
+/// its gas estimation failed or its transaction reverted.
+#[derive(thiserror::Error, Debug)]
+#[error("{0}")]
+pub struct Reverted(String);
+
+/// Sends a transaction calling `func` of the `contract` with estimated gas and fees.
+/// Fails with `Reverted` if the transaction is reverted.
+pub async fn send_call<T, P>(
+    id: &ReqId,
+    web3: &Web3<T>,
+    contract: &Contract<T>,
+    func: &str,
+    params: P,
+    admin_key: &SecretKey,
+) -> Result<TransactionReceipt>
+where
+    T: Transport,
+    P: Tokenize + Clone,
+{
+    debug!("{} Estimating gas for {}...", id, func);
+    let gas = contract
+        .estimate_gas(
+            func,
+            params.clone(),
+            SecretKeyRef::new(admin_key).address(),
+            Options::default(),
         )
         .await
         .map_err(|e| {
-            error!("{} Failed signed_call_with_confirmations: {}", id, e);
-            e
+            error!("{} Failed estimate_gas: {}", id, e);
+            Reverted(format!("Failed estimate_gas of {}: {}", func, e))
         })?;
+    let data = contract
+        .abi()
+        .function(func)?
+        .encode_input(&params.into_tokens())?;
+    let mut tx = TransactionParameters {
+        to: Some(contract.address()),
+        gas: apply_gas_multiplier(gas, config::web3_gas_multiplier()),
+        data: data.into(),
+        ..TransactionParameters::default()
+    };
+    if config::web3_eip1559() {
+        if let Some((max_fee, max_priority_fee)) = eip1559_fees(id, &web3.eth()).await {
+            tx.transaction_type = Some(U64::from(2));
+            tx.max_fee_per_gas = Some(max_fee);
+            tx.max_priority_fee_per_gas = Some(max_priority_fee);
+        }
+    }
 
-    info!("{} OK", id);
-    Ok(())
-}
-
-async fn get_decimals<T: Transport>(
-    id: &ReqId,
-    eth: Eth<T>,
-    token_address: ethereum::Address,
-) -> web3::contract::Result<u32> {
-    let token = Contract::from_json(eth, token_address, include_bytes!("../erc20/ERC20.abi"))
+    debug!("{} Sending transaction {} with gas {}...", id, func, tx.gas);
+    // Tokens live on the EVM of `web3.rpc_url`, the default network
+    let confirmations = config::solana_evm_confirmations(config::DEFAULT_NETWORK);
+    let receipt = eth_nonce::send(id, web3, tx, admin_key, confirmations)
+        .await
         .map_err(|e| {
-            error!("{} Failed reading ERC20.abi: {}", id, e);
+            error!("{} Failed sending {}: {}", id, func, e);
             e
         })?;
+    if receipt.status != Some(U64::from(1)) {
+        return Err(Reverted(format!(
+            "Transaction {:?} failed with status {:?}",
+            receipt.transaction_hash, receipt.status
+        ))
+        .into());
+    }
+
+    info!("{} OK {:?}", id, receipt.transaction_hash);
+    Ok(receipt)
+}
+
+/// Multiplies estimated `gas` by the safety `multiplier`.
+fn apply_gas_multiplier(gas: U256, multiplier: f64) -> U256 {
+    let percent = (multiplier * 100.0).round() as u64;
+    gas.saturating_mul(U256::from(percent)) / 100
+}
+
+#[test]
+fn test_apply_gas_multiplier() {
+    assert_eq!(
+        apply_gas_multiplier(U256::from(100_000), 1.0),
+        U256::from(100_000)
+    );
+    assert_eq!(
+        apply_gas_multiplier(U256::from(100_000), 1.25),
+        U256::from(125_000)
+    );
+    assert_eq!(apply_gas_multiplier(U256::from(3), 1.5), U256::from(4));
+}
+
+/// Number of recent blocks to estimate fees.
+const FEE_HISTORY_BLOCKS: u64 = 10;
+/// Percentile of priority fees paid in recent blocks.
+const FEE_HISTORY_PERCENTILE: f64 = 50.0;
+
+/// Returns max fee and max priority fee per gas for an EIP-1559 transaction.
+/// Falls back to configured caps if the node doesn't support `eth_feeHistory`.
+async fn eip1559_fees<T: Transport>(id: &ReqId, eth: &Eth<T>) -> Option<(U256, U256)> {
+    let max_fee_cap = U256::from(config::web3_max_fee_per_gas());
+    let max_priority_fee_cap = U256::from(config::web3_max_priority_fee_per_gas());
+
+    let history = eth
+        .fee_history(
+            U256::from(FEE_HISTORY_BLOCKS),
+            BlockNumber::Latest,
+            Some(vec![FEE_HISTORY_PERCENTILE]),
+        )
+        .await;
+    match history {
+        Ok(history) => Some(fees_from_history(
+            &history,
+            max_fee_cap,
+            max_priority_fee_cap,
+        )),
+        Err(e) => {
+            warn!("{} Failed eth_feeHistory: {}", id, e);
+            if max_fee_cap.is_zero() || max_priority_fee_cap.is_zero() {
+                warn!(
+                    "{} No fee caps configured; legacy transaction will be sent",
+                    id
+                );
+                None
+            } else {
+                Some((max_fee_cap, max_priority_fee_cap))
+            }
+        }
+    }
+}
+
+/// Computes fees from the median priority fee and the next block base fee.
+/// Zero caps are ignored.
+fn fees_from_history(
+    history: &FeeHistory,
+    max_fee_cap: U256,
+    max_priority_fee_cap: U256,
+) -> (U256, U256) {
+    let base_fee = history.base_fee_per_gas.last().copied().unwrap_or_default();
+    let mut rewards = history
+        .reward
+        .iter()
+        .flatten()
+        .filter_map(|r| r.first().copied())
+        .collect::<Vec<_>>();
+    rewards.sort();
+
+    let mut max_priority_fee = rewards.get(rewards.len() / 2).copied().unwrap_or_default();
+    if !max_priority_fee_cap.is_zero() {
+        max_priority_fee = max_priority_fee.min(max_priority_fee_cap);
+    }
+    let mut max_fee = base_fee
+        .saturating_mul(U256::from(2))
+        .saturating_add(max_priority_fee);
+    if !max_fee_cap.is_zero() {
+        max_fee = max_fee.min(max_fee_cap);
+    }
+
+    (max_fee, max_priority_fee.min(max_fee))
+}
+
+#[test]
+fn test_fees_from_history() {
+    let history = FeeHistory {
+        oldest_block: BlockNumber::Number(1.into()),
+        base_fee_per_gas: vec![100.into(), 110.into(), 120.into()],
+        gas_used_ratio: vec![0.5, 0.5],
+        reward: Some(vec![vec![3.into()], vec![1.into()], vec![2.into()]]),
+    };
+
+    let (max_fee, max_priority_fee) = fees_from_history(&history, U256::zero(), U256::zero());
+    assert_eq!(max_priority_fee, U256::from(2));
+    assert_eq!(max_fee, U256::from(242));
+
+    let (max_fee, max_priority_fee) = fees_from_history(&history, U256::from(200), U256::from(1));
+    assert_eq!(max_priority_fee, U256::from(1));
+    assert_eq!(max_fee, U256::from(200));
+}
+
+async fn get_decimals(id: &ReqId, token_address: ethereum::Address) -> Result<u32> {
+    let token = web3_client::contract(Abi::Erc20, token_address)?;
 
     let decimals = token
         .query("decimals", (), None, Options::default(), None)
-        .await?;
+        .await
+        .map_err(|e| match e {
+            web3::contract::Error::Api(e) => eyre::Report::from(e),
+            e => NotToken(format!("Failed reading decimals: {}", e)).into(),
+        })?;
     debug!(
         "{} ERC20 token {} has decimals {}",
         id, token_address, decimals
@@ -166,20 +1161,131 @@ async fn get_decimals<T: Transport>(
     Ok(decimals)
 }
 
-/// Returns multiplication factor to convert whole token value to fractions.
-async fn multiplication_factor(token_address: &str) -> Result<u64> {
-    let decimals = {
-        TOKENS
-            .read()
+/// Reads symbol and name of the token; both are optional in ERC20, so failures are ignored.
+async fn get_metadata(id: &ReqId, token_address: ethereum::Address) -> (String, String) {
+    let token = match web3_client::contract(Abi::Erc20, token_address) {
+        Ok(token) => token,
+        Err(e) => {
+            error!("{} {}", id, e);
+            return Default::default();
+        }
+    };
+
+    let mut metadata = Vec::with_capacity(2);
+    for func in ["symbol", "name"] {
+        let value: String = token
+            .query(func, (), None, Options::default(), None)
             .await
-            .get(token_address)
-            .ok_or_else(|| eyre!("Token info in cache not found: {}", token_address))?
-            .decimals
+            .unwrap_or_else(|e| {
+                warn!(
+                    "{} ERC20 token {} has no {}: {}",
+                    id, token_address, func, e
+                );
+                String::default()
+            });
+        metadata.push(value);
+    }
+    debug!(
+        "{} ERC20 token {} has symbol '{}' and name '{}'",
+        id, token_address, metadata[0], metadata[1]
+    );
+
+    let name = metadata.pop().unwrap_or_default();
+    let symbol = metadata.pop().unwrap_or_default();
+    (symbol, name)
+}
+
+/// Returns decimals of the token from the local cache.
+async fn decimals(token_address: &str) -> Result<u32> {
+    if let Some(token) = TOKENS.read().await.get(token_address) {
+        return Ok(token.decimals);
+    }
+    Err(eyre!(
+        "Token {} is unavailable: {}",
+        token_address,
+        unavailable_reason(token_address).await
+    ))
+}
+
+/// Converts whole tokens into base units of a token with `decimals`.
+fn whole_to_base_units(whole: u64, decimals: u32) -> Result<U256> {
+    U256::from(10)
+        .checked_pow(U256::from(decimals))
+        .and_then(|factor| factor.checked_mul(U256::from(whole)))
+        .ok_or_else(|| eyre!("Overflow {} * 10^{}", whole, decimals))
+}
+
+/// Parses the amount exactly into base units of a token with `decimals`.
+fn parse_amount(
+    amount: &Amount,
+    decimals: u32,
+    in_base_units: bool,
+) -> Result<U256, InvalidAmount> {
+    let text = match amount {
+        Amount::Number(n) => n.to_string(),
+        Amount::Text(s) => s.trim().to_string(),
     };
-    let factor = 10_u64
-        .checked_pow(decimals)
-        .ok_or_else(|| eyre!("Token {} overflow 10^{}", token_address, decimals))?;
-    Ok(factor)
+    let invalid = |reason: String| InvalidAmount {
+        amount: text.clone(),
+        reason,
+    };
+
+    let (whole, fraction) = text.split_once('.').unwrap_or((text.as_str(), ""));
+    let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+        return Err(invalid("not a decimal number".into()));
+    }
+
+    let scale = if in_base_units { 0 } else { decimals as usize };
+    if fraction.len() > scale {
+        return Err(invalid(format!("more than {} fractional digits", scale)));
+    }
+
+    let digits = format!(
+        "{}{}{}",
+        whole,
+        fraction,
+        "0".repeat(scale - fraction.len())
+    );
+    U256::from_dec_str(&digits).map_err(|_| invalid("overflow".into()))
+}
+
+#[test]
+fn test_parse_amount() {
+    let text = |s: &str| Amount::Text(s.into());
+
+    assert_eq!(
+        parse_amount(&Amount::Number(2), 6, false).unwrap(),
+        U256::from(2_000_000)
+    );
+    assert_eq!(
+        parse_amount(&Amount::Number(2), 6, true).unwrap(),
+        U256::from(2)
+    );
+    assert_eq!(
+        parse_amount(&text("0.25"), 6, false).unwrap(),
+        U256::from(250_000)
+    );
+    assert_eq!(parse_amount(&text(".5"), 1, false).unwrap(), U256::from(5));
+    assert_eq!(
+        parse_amount(&text("1.000001"), 6, false).unwrap(),
+        U256::from(1_000_001)
+    );
+    assert_eq!(
+        parse_amount(&text("123"), 6, true).unwrap(),
+        U256::from(123)
+    );
+    assert_eq!(
+        parse_amount(&text("1"), 18, false).unwrap(),
+        U256::exp10(18)
+    );
+
+    assert!(parse_amount(&text("1.0000001"), 6, false).is_err());
+    assert!(parse_amount(&text("1.5"), 6, true).is_err());
+    assert!(parse_amount(&text("-1"), 6, false).is_err());
+    assert!(parse_amount(&text("1e3"), 6, false).is_err());
+    assert!(parse_amount(&text("."), 6, false).is_err());
+    assert!(parse_amount(&text(&"9".repeat(80)), 0, false).is_err());
 }
 
 use derive_new::new;
@@ -188,10 +1294,41 @@ use futures_locks::RwLock;
 #[derive(new, Debug, Default, Clone)]
 struct Token {
     decimals: u32,
+    symbol: String,
+    name: String,
+    /// Flag that the faucet is allowed to mint the token.
+    mintable: bool,
 }
 
 type Tokens = std::collections::HashMap<String, Token>;
 
+/// Interval after which an unavailable token may be checked again on demand.
+const RECHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Represents a token which failed the check.
+struct Unavailable {
+    reason: String,
+    checked: Instant,
+}
+
+/// Amounts of tokens in base units distributed to recipients in the current time slice.
+struct Distributed {
+    since: Instant,
+    amounts: std::collections::HashMap<(String, ethereum::Address), U256>,
+}
+
+impl Default for Distributed {
+    fn default() -> Self {
+        Self {
+            since: Instant::now(),
+            amounts: Default::default(),
+        }
+    }
+}
+
 lazy_static::lazy_static! {
     static ref TOKENS: RwLock<Tokens> = RwLock::new(Tokens::default());
+    static ref UNAVAILABLE: RwLock<std::collections::HashMap<String, Unavailable>> =
+        RwLock::new(std::collections::HashMap::new());
+    static ref DISTRIBUTED: RwLock<Distributed> = RwLock::new(Distributed::default());
 }
@@ -0,0 +1,397 @@
+//! Faucet Ethereum nonce manager module.
+//!
+//! All transactions of the faucet are signed by the same `web3.private_key`,
+//! so nonces are handed out here sequentially instead of being fetched
+//! by each request on its own.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use eyre::{eyre, Result};
+use tokio::sync::Mutex;
+use tracing::{debug, info, warn};
+
+use secp256k1::SecretKey;
+use web3::api::Eth;
+use web3::signing::{Key as _, SecretKeyRef};
+use web3::types::{
+    Address, BlockNumber, Bytes, TransactionParameters, TransactionReceipt, H256, U256,
+};
+use web3::{Transport, Web3};
+
+use crate::events::{self, Event};
+use crate::{config, id::ReqId, web3_client};
+
+/// Number of attempts to send a transaction after a nonce collision.
+const MAX_NONCE_RETRIES: usize = 3;
+
+/// Interval between polls of a transaction receipt without a new blocks subscription.
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Longest expected interval between blocks; bounds the wait for confirmations.
+const MAX_BLOCK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Gas of a plain value transfer used to cancel stuck transactions.
+const CANCEL_GAS: u64 = 21_000;
+
+lazy_static::lazy_static! {
+    static ref NONCES: Mutex<HashMap<Address, U256>> = Mutex::new(HashMap::new());
+}
+
+/// Hands out the next nonce of the `sender`.
+/// Fetches the pending transaction count from the node first time or after a resync.
+pub async fn next<T: Transport>(eth: &Eth<T>, sender: Address) -> web3::Result<U256> {
+    let mut nonces = NONCES.lock().await;
+    let nonce = match nonces.get(&sender) {
+        Some(nonce) => *nonce,
+        None => {
+            eth.transaction_count(sender, Some(BlockNumber::Pending))
+                .await?
+        }
+    };
+    nonces.insert(sender, nonce + 1);
+    Ok(nonce)
+}
+
+/// Forgets the cached nonce of the `sender`, so the next one is read from the node.
+pub async fn resync(id: &ReqId, sender: Address) {
+    warn!("{} Resyncing nonce of {:?}", id, sender);
+    NONCES.lock().await.remove(&sender);
+}
+
+/// Signs and sends a transaction with the next nonce of the `key` owner,
+/// then waits for its receipt and `confirmations` blocks on top of it.
+/// A stuck transaction is re-broadcast with bumped fees up to `web3.max_replacements`
+/// times, then it's awaited or, if `web3.cancel_stuck` is set, cancelled.
+pub async fn send<T: Transport>(
+    id: &ReqId,
+    web3: &Web3<T>,
+    mut tx: TransactionParameters,
+    key: &SecretKey,
+    confirmations: u64,
+) -> Result<TransactionReceipt> {
+    let sender = SecretKeyRef::new(key).address();
+    if tx.transaction_type.is_none() && tx.gas_price.is_none() {
+        tx.gas_price = Some(web3.eth().gas_price().await?);
+    }
+
+    let mut retries = 0;
+    let hash = loop {
+        tx.nonce = Some(next(&web3.eth(), sender).await?);
+        match broadcast(id, web3, &tx, key).await {
+            Ok(hash) => break hash,
+            Err(e) if is_nonce_error(&e) && retries < MAX_NONCE_RETRIES => {
+                warn!("{} Nonce {:?} rejected: {}", id, tx.nonce, e);
+                resync(id, sender).await;
+                retries += 1;
+            }
+            Err(e) => {
+                // The nonce was not used: resync to avoid a gap.
+                resync(id, sender).await;
+                return Err(e.into());
+            }
+        }
+    };
+
+    let receipt = wait_or_replace(id, web3, tx, key, sender, hash).await?;
+    let receipt = wait_confirmations(id, &web3.eth(), receipt, confirmations).await?;
+    let signature = format!("{:?}", receipt.transaction_hash);
+    events::publish(id, Event::Confirmed { signature });
+    Ok(receipt)
+}
+
+/// Waits until the block of the receipt gets `confirmations` blocks on top of it.
+/// Fails if the transaction is dropped from the chain meanwhile
+/// or the chain doesn't grow by `confirmations` blocks in time.
+async fn wait_confirmations<T: Transport>(
+    id: &ReqId,
+    eth: &Eth<T>,
+    receipt: TransactionReceipt,
+    confirmations: u64,
+) -> Result<TransactionReceipt> {
+    let mined = match receipt.block_number {
+        Some(mined) if confirmations > 0 => mined.as_u64(),
+        _ => return Ok(receipt),
+    };
+
+    debug!(
+        "{} Waiting for {} confirmations of {:?}...",
+        id, confirmations, receipt.transaction_hash
+    );
+    let timeout = Duration::from_secs(MAX_BLOCK_INTERVAL.as_secs().saturating_mul(confirmations));
+    let start = Instant::now();
+    let mut heads = web3_client::new_heads(id).await;
+    loop {
+        let current = eth.block_number().await?.as_u64();
+        if current >= mined + confirmations {
+            break;
+        }
+        if start.elapsed() >= timeout {
+            return Err(eyre!(
+                "Transaction {:?} got {} of {} confirmations in {:?}",
+                receipt.transaction_hash,
+                current.saturating_sub(mined),
+                confirmations,
+                timeout
+            ));
+        }
+        web3_client::next_block(&mut heads, RECEIPT_POLL_INTERVAL).await;
+    }
+
+    match eth.transaction_receipt(receipt.transaction_hash).await? {
+        Some(confirmed) if confirmed.block_hash.is_some() => Ok(confirmed),
+        _ => Err(eyre!(
+            "Transaction {:?} has been dropped from the chain",
+            receipt.transaction_hash
+        )),
+    }
+}
+
+/// Waits for a receipt of any version of the transaction,
+/// re-broadcasting or cancelling it if it gets stuck.
+async fn wait_or_replace<T: Transport>(
+    id: &ReqId,
+    web3: &Web3<T>,
+    mut tx: TransactionParameters,
+    key: &SecretKey,
+    sender: Address,
+    hash: H256,
+) -> Result<TransactionReceipt> {
+    let timeout = Duration::from_secs(config::web3_stuck_timeout_secs());
+    let mut hashes = vec![hash];
+
+    for replacement in 1..=config::web3_max_replacements() {
+        if let Some(receipt) = wait_receipt(id, &web3.eth(), &hashes, timeout).await? {
+            return Ok(receipt);
+        }
+        if !bump_fees(&mut tx) {
+            warn!(
+                "{} Transaction with nonce {:?} is stuck at the fee cap",
+                id, tx.nonce
+            );
+            return wait_mined(id, &web3.eth(), &hashes, timeout).await;
+        }
+        warn!(
+            "{} Transaction with nonce {:?} is stuck; replacement {}",
+            id, tx.nonce, replacement
+        );
+        match broadcast(id, web3, &tx, key).await {
+            Ok(hash) => hashes.push(hash),
+            Err(e) => warn!("{} Failed replacement: {}", id, e),
+        }
+    }
+
+    if !config::web3_cancel_stuck() {
+        return wait_mined(id, &web3.eth(), &hashes, timeout).await;
+    }
+    if let Some(receipt) = wait_receipt(id, &web3.eth(), &hashes, timeout).await? {
+        return Ok(receipt);
+    }
+
+    let mut cancel = TransactionParameters {
+        nonce: tx.nonce,
+        to: Some(sender),
+        gas: U256::from(CANCEL_GAS),
+        value: U256::zero(),
+        data: Bytes::default(),
+        access_list: None,
+        ..tx.clone()
+    };
+    if !bump_fees(&mut cancel) {
+        warn!(
+            "{} Transaction with nonce {:?} can't be cancelled at the fee cap",
+            id, tx.nonce
+        );
+        return wait_mined(id, &web3.eth(), &hashes, timeout).await;
+    }
+    warn!("{} Cancelling transaction with nonce {:?}", id, tx.nonce);
+    let cancel_hash = match broadcast(id, web3, &cancel, key).await {
+        Ok(cancel_hash) => {
+            hashes.push(cancel_hash);
+            Some(cancel_hash)
+        }
+        Err(e) => {
+            warn!("{} Failed cancellation: {}", id, e);
+            None
+        }
+    };
+
+    let receipt = wait_mined(id, &web3.eth(), &hashes, timeout).await?;
+    if Some(receipt.transaction_hash) == cancel_hash {
+        return Err(eyre!(
+            "Transaction with nonce {:?} was stuck and cancelled",
+            tx.nonce
+        ));
+    }
+    Ok(receipt)
+}
+
+/// Waits for a receipt of any of the pending `hashes` without a deadline:
+/// they hold the nonce, so one of them is mined sooner or later.
+async fn wait_mined<T: Transport>(
+    id: &ReqId,
+    eth: &Eth<T>,
+    hashes: &[H256],
+    interval: Duration,
+) -> Result<TransactionReceipt> {
+    loop {
+        if let Some(receipt) = wait_receipt(id, eth, hashes, interval).await? {
+            return Ok(receipt);
+        }
+        warn!("{} Transactions {:?} are still pending", id, hashes);
+    }
+}
+
+/// Signs and sends the transaction, returns its hash.
+async fn broadcast<T: Transport>(
+    id: &ReqId,
+    web3: &Web3<T>,
+    tx: &TransactionParameters,
+    key: &SecretKey,
+) -> web3::Result<H256> {
+    let signed = web3.accounts().sign_transaction(tx.clone(), key).await?;
+    events::publish(id, Event::TxBuilt);
+    debug!(
+        "{} Sending transaction {:?} with nonce {:?}...",
+        id, signed.transaction_hash, tx.nonce
+    );
+    let hash = match web3
+        .eth()
+        .send_raw_transaction(signed.raw_transaction)
+        .await
+    {
+        Ok(hash) => hash,
+        Err(e) if is_known_error(&e) => {
+            // The node has accepted this very transaction before.
+            signed.transaction_hash
+        }
+        Err(e) => return Err(e),
+    };
+    info!("{} Sent transaction {:?}", id, hash);
+    let signature = format!("{:?}", hash);
+    events::publish(id, Event::SignatureSent { signature });
+    Ok(hash)
+}
+
+/// Checks receipts of `hashes` on every new block until one of them is mined
+/// or `timeout` elapses.
+async fn wait_receipt<T: Transport>(
+    id: &ReqId,
+    eth: &Eth<T>,
+    hashes: &[H256],
+    timeout: Duration,
+) -> web3::Result<Option<TransactionReceipt>> {
+    let start = Instant::now();
+    let mut heads = web3_client::new_heads(id).await;
+    loop {
+        for hash in hashes {
+            if let Some(receipt) = eth.transaction_receipt(*hash).await? {
+                if receipt.block_number.is_some() {
+                    return Ok(Some(receipt));
+                }
+            }
+        }
+        if start.elapsed() >= timeout {
+            return Ok(None);
+        }
+        web3_client::next_block(&mut heads, RECEIPT_POLL_INTERVAL).await;
+    }
+}
+
+/// Checks if the node rejected a transaction because of its nonce.
+fn is_nonce_error(e: &web3::Error) -> bool {
+    match e {
+        web3::Error::Rpc(e) => is_nonce_message(&e.message),
+        _ => false,
+    }
+}
+
+/// Checks if the node error message is about the transaction nonce.
+fn is_nonce_message(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("nonce too low") || message.contains("nonce too high")
+}
+
+/// Checks if the node rejected a transaction because it already has it.
+fn is_known_error(e: &web3::Error) -> bool {
+    match e {
+        web3::Error::Rpc(e) => is_known_message(&e.message),
+        _ => false,
+    }
+}
+
+/// Checks if the node error message says the transaction is already known.
+fn is_known_message(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("already known") || message.contains("known transaction")
+}
+
+/// Increases fees of the transaction by `web3.fee_bump_percent`.
+/// Returns false if a fee would exceed its configured cap.
+fn bump_fees(tx: &mut TransactionParameters) -> bool {
+    let percent = config::web3_fee_bump_percent();
+    let max_fee_cap = U256::from(config::web3_max_fee_per_gas());
+    let max_priority_fee_cap = U256::from(config::web3_max_priority_fee_per_gas());
+
+    let bump = |fee: Option<U256>, cap: U256| match fee {
+        Some(fee) => bump_fee(fee, percent, cap).map(Some),
+        None => Some(None),
+    };
+    match (
+        bump(tx.gas_price, max_fee_cap),
+        bump(tx.max_fee_per_gas, max_fee_cap),
+        bump(tx.max_priority_fee_per_gas, max_priority_fee_cap),
+    ) {
+        (Some(gas_price), Some(max_fee), Some(max_priority_fee)) => {
+            tx.gas_price = gas_price;
+            tx.max_fee_per_gas = max_fee;
+            tx.max_priority_fee_per_gas = max_priority_fee;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Increases `fee` by `percent`; returns `None` if the result exceeds non-zero `cap`.
+fn bump_fee(fee: U256, percent: u64, cap: U256) -> Option<U256> {
+    let bumped = fee.saturating_mul(U256::from(100 + percent)) / 100 + 1;
+    if !cap.is_zero() && bumped > cap {
+        None
+    } else {
+        Some(bumped)
+    }
+}
+
+#[test]
+fn test_bump_fee() {
+    assert_eq!(
+        bump_fee(U256::from(100), 25, U256::zero()),
+        Some(U256::from(126))
+    );
+    assert_eq!(
+        bump_fee(U256::zero(), 25, U256::zero()),
+        Some(U256::from(1))
+    );
+    assert_eq!(
+        bump_fee(U256::from(100), 10, U256::from(111)),
+        Some(U256::from(111))
+    );
+    assert_eq!(bump_fee(U256::from(100), 25, U256::from(120)), None);
+}
+
+#[test]
+fn test_is_nonce_message() {
+    assert!(is_nonce_message("nonce too low"));
+    assert!(is_nonce_message("Nonce too high"));
+    assert!(!is_nonce_message("already known"));
+    assert!(!is_nonce_message(
+        "insufficient funds for gas * price + value"
+    ));
+}
+
+#[test]
+fn test_is_known_message() {
+    assert!(is_known_message("already known"));
+    assert!(is_known_message("Known transaction: 0x12ab"));
+    assert!(!is_known_message("nonce too low"));
+}
@@ -1,6 +1,6 @@
 //! Faucet Ethereum utilities module.
 
-use eyre::Result;
+use eyre::{eyre, Result};
 
 pub type Address = web3::types::Address;
 
@@ -35,3 +35,49 @@ fn test_address_from_str() {
     let r = address_from_str("0x00000000000000000000000000000000DeadBeef");
     assert!(r.is_ok());
 }
+
+/// Encodes the address in hex with the EIP-55 mixed-case checksum.
+pub fn to_checksum(address: &Address) -> String {
+    let hex = hex::encode(address.as_bytes());
+    let hash = web3::signing::keccak256(hex.as_bytes());
+    let mut result = String::with_capacity(hex.len() + 2);
+    result.push_str("0x");
+    for (i, c) in hex.chars().enumerate() {
+        let nibble = if i % 2 == 0 {
+            hash[i / 2] >> 4
+        } else {
+            hash[i / 2] & 0x0f
+        };
+        if nibble >= 8 {
+            result.push(c.to_ascii_uppercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Converts string representation of address to the H160 hash format.
+/// Mixed-case addresses must have a valid EIP-55 checksum.
+pub fn address_from_str_checked(s: &str) -> Result<Address> {
+    let address = address_from_str(s)?;
+    let hex = strip_0x_prefix(s);
+    let mixed_case =
+        hex.chars().any(|c| c.is_ascii_lowercase()) && hex.chars().any(|c| c.is_ascii_uppercase());
+    if mixed_case && strip_0x_prefix(&to_checksum(&address)) != hex {
+        return Err(eyre!("Invalid checksum of address {}", s));
+    }
+    Ok(address)
+}
+
+#[test]
+fn test_address_checksum() {
+    const CHECKSUMMED: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+    let address = address_from_str(CHECKSUMMED).unwrap();
+    assert_eq!(to_checksum(&address), CHECKSUMMED);
+
+    assert!(address_from_str_checked(CHECKSUMMED).is_ok());
+    assert!(address_from_str_checked(&CHECKSUMMED.to_lowercase()).is_ok());
+    assert!(address_from_str_checked(&CHECKSUMMED.to_uppercase().replace("0X", "0x")).is_ok());
+    assert!(address_from_str_checked("0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed").is_err());
+}
@@ -0,0 +1,209 @@
+//! Faucet events module.
+//!
+//! Publishes lifecycle events of requests to watchers of a single request
+//! and to the global feed of all faucet activity.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio::sync::broadcast;
+
+use crate::id::ReqId;
+
+/// Time events of a finished request (or of a watched one which never came) are kept.
+const RETENTION: Duration = Duration::from_secs(60);
+
+/// Capacity of the channel of a single request.
+const REQUEST_CAPACITY: usize = 16;
+
+/// Capacity of the global feed; slow watchers skip older events.
+const FEED_CAPACITY: usize = 1024;
+
+lazy_static::lazy_static! {
+    static ref REQUESTS: Mutex<HashMap<String, Channel>> = Mutex::new(HashMap::new());
+    static ref FEED: broadcast::Sender<Record> = broadcast::channel(FEED_CAPACITY).0;
+}
+
+/// Represents a lifecycle event of a request.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    Accepted,
+    RateCheckPassed,
+    TxBuilt,
+    /// Signature of a Solana transaction or hash of an Ethereum transaction.
+    SignatureSent {
+        signature: String,
+    },
+    Confirmed {
+        signature: String,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+impl Event {
+    /// Returns the name of the event.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::Accepted => "accepted",
+            Event::RateCheckPassed => "rate_check_passed",
+            Event::TxBuilt => "tx_built",
+            Event::SignatureSent { .. } => "signature_sent",
+            Event::Confirmed { .. } => "confirmed",
+            Event::Failed { .. } => "failed",
+        }
+    }
+}
+
+/// Represents an event of the request with the id.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Record {
+    pub req_id: String,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+impl Record {
+    /// Formats the record as a message of Server-Sent Events.
+    pub fn to_sse(&self) -> String {
+        let data = serde_json::to_string(self).unwrap_or_default();
+        format!("event: {}\ndata: {}\n\n", self.event.name(), data)
+    }
+}
+
+/// Represents events of a single request.
+struct Channel {
+    history: Vec<Record>,
+    /// Dropped when the request is finished to end the streams of watchers.
+    sender: Option<broadcast::Sender<Record>>,
+    /// Number of trackers of the request in progress.
+    trackers: usize,
+    /// Time to forget the finished request.
+    expires: Instant,
+}
+
+impl Channel {
+    fn new() -> Self {
+        Self {
+            history: Vec::new(),
+            sender: Some(broadcast::channel(REQUEST_CAPACITY).0),
+            trackers: 0,
+            expires: Instant::now() + RETENTION,
+        }
+    }
+}
+
+/// Tracks a request in progress; its watchers' streams end when all its trackers are dropped.
+pub struct Tracker {
+    id: ReqId,
+}
+
+impl Drop for Tracker {
+    fn drop(&mut self) {
+        let mut requests = REQUESTS.lock().unwrap();
+        if let Some(channel) = requests.get_mut(self.id.as_str()) {
+            channel.trackers = channel.trackers.saturating_sub(1);
+            if channel.trackers == 0 {
+                channel.sender = None;
+                channel.expires = Instant::now() + RETENTION;
+            }
+        }
+    }
+}
+
+/// Starts tracking of the request and publishes its acceptance.
+pub fn track(id: &ReqId) -> Tracker {
+    {
+        let mut requests = REQUESTS.lock().unwrap();
+        purge(&mut requests);
+        let channel = requests
+            .entry(id.as_str().to_owned())
+            .or_insert_with(Channel::new);
+        if channel.sender.is_none() {
+            *channel = Channel::new();
+        }
+        channel.trackers += 1;
+    }
+    publish(id, Event::Accepted);
+    Tracker { id: id.clone() }
+}
+
+/// Keeps the request in progress after its handler returns (e.g. for a confirmation
+/// in background). Returns `None` if the request is not tracked.
+pub fn hold(id: &ReqId) -> Option<Tracker> {
+    let mut requests = REQUESTS.lock().unwrap();
+    let channel = requests.get_mut(id.as_str())?;
+    channel.sender.as_ref()?;
+    channel.trackers += 1;
+    Some(Tracker { id: id.clone() })
+}
+
+/// Publishes the event of the request.
+pub fn publish(id: &ReqId, event: Event) {
+    let record = Record {
+        req_id: id.as_str().to_owned(),
+        event,
+    };
+    // It fails only if nobody watches the feed
+    let _ = FEED.send(record.clone());
+
+    let mut requests = REQUESTS.lock().unwrap();
+    if let Some(channel) = requests.get_mut(id.as_str()) {
+        if let Some(sender) = &channel.sender {
+            let _ = sender.send(record.clone());
+            channel.history.push(record);
+        }
+    }
+}
+
+/// Subscribes to events of the request: returns events published so far
+/// and the receiver of next ones if the request isn't finished.
+/// Returns `None` if the request is unknown or forgotten.
+pub fn watch(request_id: &str) -> Option<(Vec<Record>, Option<broadcast::Receiver<Record>>)> {
+    let mut requests = REQUESTS.lock().unwrap();
+    purge(&mut requests);
+    let channel = requests.get(request_id)?;
+    let receiver = channel.sender.as_ref().map(|sender| sender.subscribe());
+    Some((channel.history.clone(), receiver))
+}
+
+/// Subscribes to the global feed.
+pub fn feed() -> broadcast::Receiver<Record> {
+    FEED.subscribe()
+}
+
+/// Forgets expired requests.
+fn purge(requests: &mut HashMap<String, Channel>) {
+    let now = Instant::now();
+    requests.retain(|_, channel| channel.sender.is_some() || channel.expires > now);
+}
+
+#[test]
+fn test_watch() {
+    let id = crate::id::generate();
+    assert!(watch(id.as_str()).is_none());
+
+    let tracker = track(&id);
+    let (history, receiver) = watch(id.as_str()).unwrap();
+    assert_eq!(history.len(), 1);
+    let mut receiver = receiver.unwrap();
+    let held = hold(&id).unwrap();
+    drop(tracker);
+    publish(&id, Event::RateCheckPassed);
+    assert_eq!(receiver.try_recv().unwrap().event, Event::RateCheckPassed);
+    drop(held);
+    assert!(matches!(
+        receiver.try_recv(),
+        Err(broadcast::error::TryRecvError::Closed)
+    ));
+    assert!(hold(&id).is_none());
+
+    let (history, receiver) = watch(id.as_str()).unwrap();
+    assert_eq!(history.len(), 2);
+    assert!(receiver.is_none());
+    let sse = history[0].to_sse();
+    assert!(sse.starts_with("event: accepted\ndata: {\"req_id\":"));
+}
@@ -0,0 +1,443 @@
+//! Faucet gRPC module.
+//!
+//! Serves typed RPCs for internal automation on its own port (cargo feature `grpc`),
+//! with the same core as the HTTP API.
+
+// Interceptors and handlers of tonic fail with its large `Status`
+#![allow(clippy::result_large_err)]
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::http::StatusCode;
+use eyre::Result;
+use nix::sys::signal;
+use nix::unistd::Pid;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Code, Request, Response, Status};
+use tracing::{error, info};
+
+use crate::erc20_tokens::{Amount, TokenResult};
+use crate::events::{self, Event};
+use crate::id::{self, ReqId};
+use crate::{active_requests, api, assets, erc20_tokens, server};
+
+mod proto {
+    tonic::include_proto!("faucet.v1");
+}
+
+use proto::faucet_admin_server::{FaucetAdmin, FaucetAdminServer};
+use proto::faucet_server::{Faucet, FaucetServer};
+use proto::job_status::State;
+use proto::{AirdropReply, AirdropRequest, Empty, JobStatus, StatusReply, StopRequest};
+
+/// Commitment level to wait for NEON if a streamed airdrop doesn't specify it.
+const CONFIRMED: &str = "confirmed";
+
+/// Serves the gRPC interface at the address.
+/// The admin service is served only with a non-empty admin token.
+pub async fn serve(
+    addr: SocketAddr,
+    admin_token: String,
+    limiters: Arc<assets::Limiters>,
+) -> Result<()> {
+    info!("{} Bind gRPC {}", id::default(), addr);
+
+    let admin = if admin_token.is_empty() {
+        info!("{} gRPC admin operations are disabled", id::default());
+        None
+    } else {
+        let service = AdminService {
+            limiters: limiters.clone(),
+        };
+        Some(FaucetAdminServer::with_interceptor(
+            service,
+            move |request| authorize(request, &admin_token),
+        ))
+    };
+
+    tonic::transport::Server::builder()
+        .add_service(FaucetServer::new(Service { limiters }))
+        .add_optional_service(admin)
+        .serve(addr)
+        .await?;
+    Ok(())
+}
+
+/// Passes a request of the admin service with the bearer `admin_token` in its metadata.
+fn authorize(request: Request<()>, admin_token: &str) -> Result<Request<()>, Status> {
+    let authorization = request
+        .metadata()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok());
+    if server::authorized(authorization, admin_token) {
+        Ok(request)
+    } else {
+        error!(
+            "{} gRPC Unauthenticated: invalid admin token",
+            id::default()
+        );
+        Err(Status::unauthenticated("Invalid token"))
+    }
+}
+
+/// Implements the gRPC service of the faucet.
+struct Service {
+    limiters: Arc<assets::Limiters>,
+}
+
+#[tonic::async_trait]
+impl Faucet for Service {
+    async fn status(&self, _: Request<Empty>) -> Result<Response<StatusReply>, Status> {
+        Ok(Response::new(StatusReply {
+            version: crate::version::display!().into(),
+            active_requests: active_requests::count() as i64,
+        }))
+    }
+
+    async fn list_tokens(&self, _: Request<Empty>) -> Result<Response<proto::TokenList>, Status> {
+        let id = id::generate();
+        let counter = active_requests::increment();
+        info!("{} Handling gRPC request for list of ERC20...", id);
+        info!("{} Active requests: {}", id, counter);
+
+        let list = erc20_tokens::list(&id)
+            .await
+            .map_err(|e| rejection(&id, e))?;
+        let tokens = list
+            .into_iter()
+            .map(|token| proto::Token {
+                address: token.address,
+                available: token.available,
+                reason: token.reason.unwrap_or_default(),
+                symbol: token.symbol,
+                name: token.name,
+                decimals: token.decimals,
+                max_amount: token.max_amount,
+                per_time_max_amount: token.per_time_max_amount,
+                balance: token.balance,
+            })
+            .collect();
+        Ok(Response::new(proto::TokenList { tokens }))
+    }
+
+    async fn airdrop(
+        &self,
+        request: Request<AirdropRequest>,
+    ) -> Result<Response<AirdropReply>, Status> {
+        let id = id::generate();
+        let counter = active_requests::increment();
+        info!("{} Handling gRPC request for Airdrop of assets...", id);
+        info!("{} Active requests: {}", id, counter);
+
+        let _events = events::track(&id);
+        let (peer, airdrops) = prepare(&id, request, false)?;
+        let results = assets::process(&id, &peer, &self.limiters, airdrops)
+            .await
+            .map_err(|e| rejection(&id, e))?;
+        Ok(Response::new(AirdropReply {
+            results: results.into_iter().map(asset_result).collect(),
+        }))
+    }
+
+    type WatchAirdropStream = ReceiverStream<Result<JobStatus, Status>>;
+
+    async fn watch_airdrop(
+        &self,
+        request: Request<AirdropRequest>,
+    ) -> Result<Response<Self::WatchAirdropStream>, Status> {
+        let id = id::generate();
+        let counter = active_requests::increment();
+        info!(
+            "{} Handling gRPC request for watched Airdrop of assets...",
+            id
+        );
+        info!("{} Active requests: {}", id, counter);
+
+        let tracker = events::track(&id);
+        let (peer, airdrops) = prepare(&id, request, true)?;
+        let checked = assets::check_all(&id, &peer, &self.limiters, airdrops)
+            .await
+            .map_err(|e| rejection(&id, e))?;
+
+        // The job goes on if the client disconnects: its caps are already taken.
+        let limiters = self.limiters.clone();
+        let (tx, rx) = mpsc::channel(16);
+        tokio::spawn(async move {
+            let _guards = (counter, tracker);
+            let _ = tx.send(Ok(job_status(State::Accepted, 0, None))).await;
+            for (index, airdrop) in checked.into_iter().enumerate() {
+                let index = index as u32;
+                let _ = tx.send(Ok(job_status(State::Sending, index, None))).await;
+                for result in assets::send(&id, &peer, &limiters, airdrop).await {
+                    let state = match result.result.error {
+                        None => State::Confirmed,
+                        Some(_) => State::Failed,
+                    };
+                    let result = Some(asset_result(result));
+                    let _ = tx.send(Ok(job_status(state, index, result))).await;
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+/// Implements the gRPC admin service of the faucet; it requires the admin token.
+struct AdminService {
+    limiters: Arc<assets::Limiters>,
+}
+
+#[tonic::async_trait]
+impl FaucetAdmin for AdminService {
+    async fn clear_caps(&self, _: Request<Empty>) -> Result<Response<Empty>, Status> {
+        let id = id::generate();
+        info!("{} Clearing airdrop limiter caches by gRPC request", id);
+        for limiter in self.limiters.neon.values() {
+            limiter.write().await.clear_cache();
+        }
+        self.limiters.spl.clear_caches().await;
+        self.limiters.sol.write().await.clear_cache();
+        self.limiters.nft.clear_caches().await;
+        Ok(Response::new(Empty {}))
+    }
+
+    async fn stop(&self, request: Request<StopRequest>) -> Result<Response<Empty>, Status> {
+        let id = id::generate();
+        info!("{} Shutting down by gRPC request...", id);
+
+        let delay = request.into_inner().delay_ms;
+        if delay > 0 {
+            info!("{} Sleeping {} millis...", id, delay);
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+        }
+
+        signal::kill(Pid::this(), signal::SIGTERM).map_err(|err| {
+            error!("{} gRPC Internal (terminate): {}", id, err);
+            Status::internal(err.to_string())
+        })?;
+        Ok(Response::new(Empty {}))
+    }
+}
+
+/// Extracts the peer and converts the requested assets into airdrops.
+/// Streamed airdrops wait for confirmation of NEON.
+fn prepare(
+    id: &ReqId,
+    request: Request<AirdropRequest>,
+    wait: bool,
+) -> Result<(IpAddr, Vec<assets::Airdrop>), Status> {
+    let peer = request
+        .remote_addr()
+        .map(|addr| addr.ip())
+        .ok_or_else(|| Status::invalid_argument("Unknown peer address"))?;
+    let airdrops = into_airdrops(id, request.into_inner(), wait)?;
+    Ok((peer, airdrops))
+}
+
+/// Converts the requested assets into airdrops.
+fn into_airdrops(
+    id: &ReqId,
+    request: AirdropRequest,
+    wait: bool,
+) -> Result<Vec<assets::Airdrop>, Status> {
+    info!("{} Processing gRPC {:?}...", id, request);
+
+    let mut requested = Vec::with_capacity(request.assets.len());
+    for asset in request.assets {
+        let kind = match proto::Kind::from_i32(asset.kind) {
+            Some(proto::Kind::Neon) => assets::Kind::Neon,
+            Some(proto::Kind::Erc20) => assets::Kind::Erc20,
+            Some(proto::Kind::Spl) => assets::Kind::Spl,
+            Some(proto::Kind::Sol) => assets::Kind::Sol,
+            Some(proto::Kind::Nft) => assets::Kind::Nft,
+            None => {
+                let err = format!("Invalid kind of asset {}", asset.kind);
+                error!("{} gRPC InvalidArgument: {}", id, err);
+                return Err(Status::invalid_argument(err));
+            }
+        };
+        let mut commitment = Some(asset.commitment).filter(|c| !c.is_empty());
+        if wait && kind == assets::Kind::Neon && commitment.is_none() {
+            commitment = Some(CONFIRMED.into());
+        }
+        requested.push(assets::Asset {
+            kind,
+            wallet: Some(asset.wallet).filter(|w| !w.is_empty()),
+            token: Some(asset.token).filter(|t| !t.is_empty()),
+            token_id: asset.token_id,
+            amount: Amount::Text(asset.amount),
+            unit: if asset.in_fractions {
+                assets::Unit::Fractions
+            } else {
+                assets::Unit::Whole
+            },
+            commitment,
+        });
+    }
+
+    let request = assets::Request {
+        wallet: request.wallet,
+        assets: requested,
+    };
+    api::Validate::validate(&request).map_err(|err| {
+        error!("{} gRPC InvalidArgument: {}", id, err);
+        Status::invalid_argument(err)
+    })?;
+    request.into_airdrops().map_err(|e| rejection(id, e))
+}
+
+/// Converts an error of a rejected request into the gRPC status.
+fn rejection(id: &ReqId, err: eyre::Report) -> Status {
+    let code = match server::rejection_status(&err) {
+        StatusCode::BAD_REQUEST => Code::InvalidArgument,
+        StatusCode::TOO_MANY_REQUESTS => Code::ResourceExhausted,
+        _ => Code::Internal,
+    };
+    error!("{} gRPC {:?}: {}", id, code, err);
+    let message = err.to_string();
+    let error = message.clone();
+    events::publish(id, Event::Failed { error });
+    Status::new(code, message)
+}
+
+fn asset_result(result: assets::AssetResult) -> proto::AssetResult {
+    let kind = match result.kind {
+        assets::Kind::Neon => proto::Kind::Neon,
+        assets::Kind::Erc20 => proto::Kind::Erc20,
+        assets::Kind::Spl => proto::Kind::Spl,
+        assets::Kind::Sol => proto::Kind::Sol,
+        assets::Kind::Nft => proto::Kind::Nft,
+    };
+    let TokenResult { token, tx, error } = result.result;
+    proto::AssetResult {
+        kind: kind as i32,
+        token,
+        tx: tx.unwrap_or_default(),
+        error: error.unwrap_or_default(),
+    }
+}
+
+fn job_status(state: State, asset: u32, result: Option<proto::AssetResult>) -> JobStatus {
+    JobStatus {
+        state: state as i32,
+        asset,
+        result,
+    }
+}
+
+#[test]
+fn test_authorize() {
+    let request = |authorization: Option<&str>| {
+        let mut request = Request::new(());
+        if let Some(authorization) = authorization {
+            let value = authorization.parse().unwrap();
+            request.metadata_mut().insert("authorization", value);
+        }
+        request
+    };
+
+    assert!(authorize(request(Some("Bearer secret")), "secret").is_ok());
+    let err = authorize(request(None), "secret").unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+    let err = authorize(request(Some("Bearer wrong")), "secret").unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+    let err = authorize(request(Some("secret")), "secret").unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+}
+
+#[test]
+fn test_into_airdrops() {
+    let id = ReqId::default();
+    let wallet = "0x4570e07200b6332989Dc04fA2a671b839D26eF0E";
+    let solana = "11111111111111111111111111111111";
+    let request = || AirdropRequest {
+        wallet: wallet.into(),
+        assets: vec![
+            proto::Asset {
+                kind: proto::Kind::Neon as i32,
+                amount: "1".into(),
+                ..Default::default()
+            },
+            proto::Asset {
+                kind: proto::Kind::Neon as i32,
+                amount: "1".into(),
+                commitment: "finalized".into(),
+                ..Default::default()
+            },
+            proto::Asset {
+                kind: proto::Kind::Erc20 as i32,
+                token: "USDT".into(),
+                amount: "0.25".into(),
+                ..Default::default()
+            },
+            proto::Asset {
+                kind: proto::Kind::Spl as i32,
+                wallet: solana.into(),
+                amount: "2".into(),
+                ..Default::default()
+            },
+            proto::Asset {
+                kind: proto::Kind::Sol as i32,
+                wallet: solana.into(),
+                amount: "3".into(),
+                in_fractions: true,
+                ..Default::default()
+            },
+            proto::Asset {
+                kind: proto::Kind::Nft as i32,
+                token: wallet.into(),
+                amount: "1".into(),
+                token_id: Some(7),
+                ..Default::default()
+            },
+        ],
+    };
+    let commitment = |airdrop: &assets::Airdrop| match airdrop {
+        assets::Airdrop::Neon(a) => a.commitment.clone(),
+        _ => panic!("NEON airdrop expected"),
+    };
+
+    // Streamed airdrops wait for NEON to be confirmed unless requested otherwise
+    let airdrops = into_airdrops(&id, request(), true).unwrap();
+    assert_eq!(airdrops.len(), 6);
+    assert_eq!(commitment(&airdrops[0]).as_deref(), Some(CONFIRMED));
+    assert_eq!(commitment(&airdrops[1]).as_deref(), Some("finalized"));
+    assert!(
+        matches!(&airdrops[2], assets::Airdrop::Erc20(a) if a.token.as_deref() == Some("USDT"))
+    );
+    assert!(matches!(&airdrops[3], assets::Airdrop::Spl(a) if a.wallet == solana && a.amount == 2));
+    assert!(matches!(&airdrops[4], assets::Airdrop::Sol(a) if a.amount == 3 && a.in_lamports));
+    assert!(matches!(&airdrops[5], assets::Airdrop::Nft(a) if a.token_id == Some(7)));
+
+    let airdrops = into_airdrops(&id, request(), false).unwrap();
+    assert_eq!(commitment(&airdrops[0]), None);
+    assert_eq!(commitment(&airdrops[1]).as_deref(), Some("finalized"));
+
+    let mut invalid = request();
+    invalid.assets[0].kind = 100;
+    let err = into_airdrops(&id, invalid, false).unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+}
+
+#[test]
+fn test_rejection() {
+    let id = ReqId::default();
+    let peer = IpAddr::from([127, 0, 0, 1]);
+
+    let err = assets::InvalidRequest("Unknown network 'third'".into());
+    assert_eq!(rejection(&id, err.into()).code(), Code::InvalidArgument);
+    let err = crate::neon_token::AirdropLimiterError::BadRequest;
+    assert_eq!(rejection(&id, err.into()).code(), Code::InvalidArgument);
+
+    let mut limiter =
+        crate::neon_token::AirdropLimiter::new(Default::default(), Default::default(), 1, 1);
+    let err = limiter.check_amount(&peer, 2).unwrap_err();
+    assert_eq!(rejection(&id, err.into()).code(), Code::ResourceExhausted);
+
+    let err = eyre::eyre!("Connection refused");
+    assert_eq!(rejection(&id, err).code(), Code::Internal);
+}
@@ -1,48 +1,110 @@
 //! Faucet id module.
 
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use tracing::error;
+use actix_web::HttpRequest;
+
+/// Header of a request id: the faucet returns its id of the request in it,
+/// the client may send its own id to mark retries of the same request.
+pub const HEADER: &str = "X-Request-Id";
 
 /// Returns empty id.
 pub fn default() -> ReqId {
     ReqId::default()
 }
 
-/// Builds a (hopefully) unique string to mark requests.
+/// Builds a random string to mark requests.
+/// It's unguessable, so only the client which got it can watch events of the request.
 pub fn generate() -> ReqId {
-    let since = SystemTime::now().duration_since(UNIX_EPOCH).map_or_else(
-        |e| {
-            error!("{{}} generate_id: time went backwards? {}", e);
-            Duration::default()
-        },
-        |s| s,
-    );
-    let digest = md5::compute(since.as_nanos().to_string());
     ReqId {
-        id: format!("{:x}", digest)[..7].to_string(),
+        id: format!("{:032x}", rand::random::<u128>()),
+        client: None,
     }
 }
 
+/// Builds a new id of the request keeping the id from the header for logs and retries.
+pub fn from_request(req: &HttpRequest) -> ReqId {
+    from_client(
+        req.headers()
+            .get(HEADER)
+            .and_then(|value| value.to_str().ok()),
+    )
+}
+
+/// Builds a new id keeping the id chosen by the client if it's valid.
+pub fn from_client(client: Option<&str>) -> ReqId {
+    ReqId {
+        client: client.filter(|value| is_valid(value)).map(str::to_owned),
+        ..generate()
+    }
+}
+
+/// Checks if the id is short and safe to print.
+fn is_valid(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 64
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[test]
+fn test_generate() {
+    let id = generate();
+    assert!(is_valid(id.as_str()));
+    assert_eq!(id.as_str().len(), 32);
+    assert_ne!(generate().as_str(), id.as_str());
+}
+
+#[test]
+fn test_from_client() {
+    let id = from_client(Some("job-42"));
+    assert_eq!(id.client(), Some("job-42"));
+    assert_ne!(id.as_str(), "job-42");
+    assert_eq!(id.as_str().len(), 32);
+    assert_eq!(from_client(Some("a b")).client(), None);
+    assert_eq!(from_client(None).client(), None);
+}
+
+#[test]
+fn test_is_valid() {
+    assert!(is_valid("3f2a7c1"));
+    assert!(is_valid("job-42_a"));
+    assert!(!is_valid(""));
+    assert!(!is_valid("a b"));
+    assert!(!is_valid("\"}"));
+    assert!(!is_valid(&"a".repeat(65)));
+}
+
 /// Represents some context: request id.
 #[derive(Default, Clone)]
 pub struct ReqId {
     id: String,
+    /// Id chosen by the client; never used as a key of events.
+    client: Option<String>,
 }
 
 impl ReqId {
     pub fn as_str(&self) -> &str {
         self.id.as_ref()
     }
+
+    /// Returns the id chosen by the client, which marks retries of the same request.
+    pub fn client(&self) -> Option<&str> {
+        self.client.as_deref()
+    }
 }
 
 use std::fmt;
 
 impl fmt::Display for ReqId {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.id.is_empty() {
-            write!(f, "{{}}")
-        } else {
-            write!(f, "{{\"req_id\": \"{}\"}}", self.id)
+        match (self.id.is_empty(), &self.client) {
+            (true, _) => write!(f, "{{}}"),
+            (false, None) => write!(f, "{{\"req_id\": \"{}\"}}", self.id),
+            (false, Some(client)) => write!(
+                f,
+                "{{\"req_id\": \"{}\", \"client_id\": \"{}\"}}",
+                self.id, client
+            ),
         }
     }
 }
@@ -4,17 +4,30 @@
 #![deny(warnings)]
 
 mod active_requests;
+mod api;
+mod assets;
 mod cli;
 mod config;
+mod erc20_batch;
 mod erc20_tokens;
+mod eth_nonce;
 mod ethereum;
+mod events;
+#[cfg(feature = "grpc")]
+mod grpc;
 mod id;
 mod log;
 mod manual;
 mod neon_token;
+mod nft_tokens;
+mod nonce;
+mod rpc;
 mod server;
+mod sol_token;
 mod solana;
+mod spl_tokens;
 mod version;
+mod web3_client;
 
 use eyre::Result;
 
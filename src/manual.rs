@@ -12,24 +12,62 @@ const MANUAL_API: &str = r##"
 A client uses standard HTTP requests to send data to the server.
 Several endpoints are supported.
 
-|:-:|:-:|-
-|**Endpoint**|**Method**|**Workload**|**Description**|
-|:-|:-:|:-:|-
-| request_ping | GET | text | Requests ping to check availability of the service
-| request_version | GET | | Requests version of the service
-| request_neon_in_galans | POST | JSON | Requests NEON tokens, amount in galans (fractions)
-| request_neon | POST | JSON | Requests NEON tokens
-| request_erc20_list | GET | | Requests list of available ERC20 tokens
-| request_erc20 | POST | JSON | Requests ERC20 tokens
-|-
+"##;
+
+const MANUAL_API_NOTES: &str = r##"
+NEON requests go to the `default` network unless the network is selected
+by the path prefix or by the `network` field of the JSON workload.
 
 Examples of JSON workload:
 ```
 { "wallet": "0x4570e07200b6332989Dc04fA2a671b839D26eF0E", "amount": 1 }
 ```
 ```
+{ "wallet": "0x4570e07200b6332989Dc04fA2a671b839D26eF0E", "amount": 1, "network": "second" }
+```
+```
+{ "wallet": "0x4570e07200b6332989Dc04fA2a671b839D26eF0E", "amount": 1, "commitment": "finalized" }
+```
+```
 { "wallet": "0x4570e07200b6332989Dc04fA2a671b839D26eF0E", "token_addr": "0x00000000000000000000000000000000CafeBabe", "amount": 10 }
 ```
+```
+{ "wallet": "0x4570e07200b6332989Dc04fA2a671b839D26eF0E", "token": "USDT", "amount": 10 }
+```
+```
+{ "wallet": "0x4570e07200b6332989Dc04fA2a671b839D26eF0E", "token": "USDT", "amount": "0.25" }
+```
+```
+{ "wallet": "0x4570e07200b6332989Dc04fA2a671b839D26eF0E", "token": "USDT", "amount": "250000", "in_base_units": true }
+```
+
+The `token` field of ERC20 requests holds a token symbol, an alias from
+`web3.token_aliases` or an address. Addresses are compared case-insensitively;
+mixed-case addresses must have a valid EIP-55 checksum.
+NFT requests hold the collection address in the `contract` field;
+ERC1155 requests may also select a configured `token_id` and an `amount`:
+```
+{ "wallet": "0x4570e07200b6332989Dc04fA2a671b839D26eF0E", "contract": "0x000000000000000000000000000000000000F00d" }
+```
+```
+{ "wallet": "0x4570e07200b6332989Dc04fA2a671b839D26eF0E", "contract": "0x000000000000000000000000000000000000F00d", "token_id": 2, "amount": 5 }
+```
+
+The ERC20 `amount` is a number of whole tokens or a decimal string;
+with `in_base_units` it is an integer amount of the token fractions.
+
+SPL requests hold a base58 Solana `wallet` and an optional `token` (mint or symbol);
+all configured SPL tokens are sent if it's missing. The response holds the signature
+of the transaction:
+```
+{ "wallet": "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin", "token": "USDC", "amount": 10 }
+```
+
+SOL requests hold a base58 Solana `wallet` and an `amount` of whole SOL
+(or lamports with `in_lamports`). The response holds the signature of the transaction:
+```
+{ "wallet": "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin", "amount": 1 }
+```
 
 Example of ping request with **curl** utility:
 ```
@@ -46,6 +84,19 @@ Example of request of list of ERC20 with **curl** utility:
 curl -i -X GET 'http://localhost:3333/request_erc20_list'
 ```
 
+Example of list of ERC20 response (balance is in token fractions):
+```
+[{ "address": "0x00000000000000000000000000000000CafeBabe", "available": true,
+   "symbol": "USDT", "name": "Tether USD", "decimals": 6, "max_amount": 1000,
+   "per_time_max_amount": 10000, "balance": "5000000000" },
+ { "address": "0x00000000000000000000000000000000DeadBeef", "available": false,
+   "reason": "Abi error: Invalid data", "symbol": "", "name": "", "decimals": 0,
+   "max_amount": 1000, "per_time_max_amount": 10000, "balance": "" }]
+```
+
+Tokens failing the check are listed as unavailable and re-checked on demand
+and every **web3**.tokens_refresh_secs.
+
 Example of NEON drop request with **curl** utility:
 ```
 curl -i -X POST \
@@ -54,6 +105,64 @@ curl -i -X POST \
     'http://localhost:3333/request_neon'
 ```
 
+Example of NEON drop request to the versioned API with **curl** utility
+(requests failing validation get 400 with the reason):
+```
+curl -i -X POST -H 'Content-Type: application/json' \
+    -d '{"wallet": "0x4570e07200b6332989Dc04fA2a671b839D26eF0E", "amount": 1}' \
+    'http://localhost:3333/v1/neon'
+```
+
+Example of request of the OpenAPI document with **curl** utility:
+```
+curl -i -X GET 'http://localhost:3333/v1/openapi.json'
+```
+
+The `rpc` endpoint serves JSON-RPC 2.0 calls, single or batched, with methods
+`faucet_version`, `faucet_listTokens` (ERC20 tokens), `faucet_requestNeon` and
+`faucet_requestErc20`. Request methods take the JSON workload of `request_neon`
+or `request_erc20` as params (an object or an array of one object) and share caps
+with the HTTP API; a cap exceeded gives error code `-32005`. A batch may hold
+up to 20 calls.
+
+Example of JSON-RPC batch request with **curl** utility:
+```
+curl -i -X POST \
+    -d '[{"jsonrpc": "2.0", "id": 1, "method": "faucet_version"}, \
+         {"jsonrpc": "2.0", "id": 2, "method": "faucet_requestNeon", \
+          "params": {"wallet": "0x4570e07200b6332989Dc04fA2a671b839D26eF0E", "amount": 1}}]' \
+    'http://localhost:3333/rpc'
+```
+
+The faucet built with cargo feature `grpc` serves the gRPC service `faucet.v1.Faucet`
+(see `proto/faucet.proto`) on **rpc**.grpc_port: status, list of ERC20 tokens,
+airdrops of assets and streamed airdrops reporting the job status until confirmation.
+It shares caps with the HTTP API. Admin operations (clearing caps of NEON, shutdown)
+are served by `faucet.v1.FaucetAdmin` only if **rpc**.admin_token is set; calls must
+carry metadata `authorization: Bearer <token>`.
+
+The faucet generates a random id of each request and returns it in the `X-Request-Id`
+header of the response. An `X-Request-Id` header of the request (letters, digits, `-`
+and `_`, up to 64 characters) is logged along with it and marks retries of the same
+request. The `events/{request_id}` endpoint streams Server-Sent Events of the request
+with the id returned by the faucet: `accepted`, `rate_check_passed`, `tx_built`,
+`signature_sent`, `confirmed` or `failed`. Events are kept for a minute after
+the request is finished (including a confirmation in background), unknown requests get 404.
+The `events` endpoint streams events of all requests to holders of **rpc**.events_token.
+
+Example of watching events of a NEON drop request with **curl** utility:
+```
+id=$(curl -s -o /dev/null -D - -X POST \
+    -d '{"wallet": "0x4570e07200b6332989Dc04fA2a671b839D26eF0E", "amount": 1}' \
+    'http://localhost:3333/request_neon' | sed -n 's/^x-request-id: //Ip' | tr -d '\r')
+curl -N "http://localhost:3333/events/$id"
+```
+
+Example of watching the global feed of events with **curl** utility:
+```
+curl -N -H 'Authorization: Bearer <token>' 'http://localhost:3333/events'
+```
+
 Example of ERC20 drop request with **curl** utility:
 ```
 curl -i -X POST \
@@ -62,6 +171,42 @@ curl -i -X POST \
          "amount": 1}' \
     'http://localhost:3333/request_erc20'
 ```
+
+Example of ERC20 response listing results per token:
+```
+[{ "token": "0x00000000000000000000000000000000CafeBabe", "tx": "0x5c50...c0de" },
+ { "token": "0x00000000000000000000000000000000DeadBeef", "error": "Insufficient funds" }]
+```
+
+The status is 200 if all tokens are sent, 207 if some of them failed
+and 500 if all of them failed.
+
+Example of multi-asset drop request with **curl** utility:
+```
+curl -i -X POST \
+    -d '{"wallet": "0x4570e07200b6332989Dc04fA2a671b839D26eF0E", \
+         "assets": [{"kind": "neon", "amount": 1}, \
+                    {"kind": "neon", "token": "second", "amount": 500, "unit": "fractions"}, \
+                    {"kind": "erc20", "token": "USDT", "amount": "0.25"}]}' \
+    'http://localhost:3333/request'
+```
+
+The `kind` of an asset is `neon`, `erc20`, `spl`, `sol` or `nft`. The `token` holds
+the network of NEON (the `default` network if missing), the ERC20 or SPL token
+(all tokens if missing) or the address of the NFT collection (required).
+The `unit` is `whole` (default) or `fractions` (galans of NEON, base units of ERC20
+and SPL, lamports of SOL); NFT are counted in tokens. NEON assets may also hold
+a `commitment` and NFT assets a `token_id` of an ERC1155 collection.
+SPL and SOL go to a Solana wallet (base58), so an asset may hold its own `wallet`
+which overrides the wallet of the request. Caps of all assets are checked
+before anything is sent, so nothing is sent if any asset is rejected.
+The response lists results per asset (per token of ERC20 and SPL assets without a token)
+with the same statuses as the ERC20 response:
+```
+[{ "kind": "neon", "token": "default" },
+ { "kind": "neon", "token": "second", "error": "Insufficient funds" },
+ { "kind": "erc20", "token": "0x00000000000000000000000000000000CafeBabe", "tx": "0x5c50...c0de" }]
+```
 "##;
 
 const MANUAL_CONFIG: &str = r##"
@@ -75,17 +220,70 @@ The configuration file should be in TOML format.
 | **rpc**.bind | Local interface TCP address
 | **rpc**.port | TCP port to listen
 | **rpc**.allowed_origins | List of client URLs that can send requests
+| **rpc**.grpc_port | TCP port of the gRPC server (built with feature `grpc`); 0 disables it
+| **rpc**.events_token | Bearer token of the global feed of events (`events` endpoint); empty disables the feed
+| **rpc**.admin_token | Bearer token of the gRPC admin service `faucet.v1.FaucetAdmin`; empty disables the service
 | **web3**.enable | Flag to on/off the entire **web3** section
 | **web3**.rpc_url | Ethereum network endpoint
+| **web3**.ws_url | Ethereum WebSocket endpoint to subscribe to new blocks instead of polling receipts (optional)
+| **web3**.request_timeout_secs | Timeout of a single request to the Ethereum endpoint in seconds (30 by default)
 | **web3**.private_key | Ethereum private key to support operations
 | **web3**.tokens | List of available ERC20 token addresses
+| **web3**.token_aliases | Table of aliases of ERC20 tokens: alias = "address"
+| **web3**.token_modes | Table of ERC20 distribution modes: "address" = `transfer` (default), `mint` or `auto` (mints if the faucet is allowed to)
 | **web3**.max_amount | Largest amount of ERC20 tokens to distribute with a single request
+| **web3**.per_time_max_amount | Largest amount of each ERC20 token to distribute to a wallet per time slice (0 means no cap)
+| **web3**.time_slice_secs | Time slice for ERC20 tokens and NFT distribution in seconds
+| **web3**.tokens_refresh_secs | Interval of re-validation of ERC20 tokens in seconds (600 by default)
+| **web3**.eip1559 | Flag to send ERC20 transfers as EIP-1559 transactions
+| **web3**.max_fee_per_gas | Cap of max fee per gas in wei (0 means no cap)
+| **web3**.max_priority_fee_per_gas | Cap of max priority fee per gas in wei (0 means no cap)
+| **web3**.gas_multiplier | Safety multiplier of estimated gas (1.25 by default)
+| **web3**.confirmations | Number of blocks to wait after a transaction is mined before the request succeeds (up to a minute per block) of the default network and of networks without their own value
+| **web3**.stuck_timeout_secs | Seconds to wait before a pending transaction is re-broadcast (60 by default)
+| **web3**.max_replacements | Number of fee-bumped re-broadcasts of a stuck transaction (0 by default)
+| **web3**.cancel_stuck | Flag to cancel a transaction still stuck after the re-broadcasts (by default it's awaited)
+| **web3**.fee_bump_percent | Fee increase of a replacement transaction in percent (25 by default, at least 10)
+| **web3**.batch_contract | Address of the deployed `erc20/Disperse.sol` contract to send ERC20 transfers in one transaction; a reverted batch is sent transfer by transfer (empty disables batching)
+| **web3**.batch_window_ms | Milliseconds to collect requests of several recipients into one batch (0 batches each request alone)
+| **web3**.nfts | List of dispensed NFT collections
+| **web3**.nfts.address | Address of the NFT collection
+| **web3**.nfts.standard | Standard of the collection: `erc721` or `erc1155`
+| **web3**.nfts.mode | `mint` (requires `mint` of OpenZeppelin presets), `inventory` (transfers pre-minted tokens) or `auto` by the MINTER_ROLE of the faucet
+| **web3**.nfts.token_ids | ERC721 inventory token ids or ERC1155 token ids to dispense
+| **web3**.nfts.max_amount | Largest amount of ERC1155 tokens per request (1 by default)
+| **web3**.nfts.per_wallet_max_amount | Largest amount of tokens of the collection per wallet and per client in a time slice (required, positive)
 | **solana**.enable | Flag to on/off the entire **solana** section
 | **solana**.url | Solana network endpoint
-| **solana**.commitment | Solana client commitment level
+| **solana**.commitment | Solana client commitment level (for reads)
+| **solana**.preflight_commitment | Commitment level of preflight checks (**solana**.commitment if empty)
+| **solana**.confirm_commitment | Commitment level to wait for (**solana**.commitment if empty)
+| **solana**.fire_and_forget | Flag to respond right after sending and confirm in background
+| **solana**.simulate | Flag to simulate deposit transactions before sending
+| **solana**.durable_nonce | Flag to use durable nonce accounts instead of latest blockhash (a retry with the same `X-Request-Id` re-sends the transaction signed before)
+| **solana**.nonce_accounts | List of nonce accounts owned by the operator
+| **solana**.nonce_accounts_count | Number of nonce accounts to create if the list is empty (1 by default)
 | **solana**.operator_keyfile | Solana keyfile to support operations
 | **solana**.evm_loader | Address of the EVM Loader program
+| **solana**.neon_backend | NEON transfer mechanism: `solana` (deposit, default) or `evm` (transfer from **web3**.private_key, default network only, 1 galan = 10E9 wei)
 | **solana**.max_amount | Largest amount of NEONs to distribute with a single request
+| **solana**.per_time_max_amount | Largest amount of NEONs to distribute to an IP address per time slice
+| **solana**.time_slice_secs | Duration of the time slice in seconds
+| **solana**.networks | List of additional Neon EVM networks (the top-level ones form the `default`)
+| **solana**.networks.name | Name of the network, used as request path prefix
+| **solana**.networks.evm_loader | Address of the EVM Loader program of the network
+| **solana**.networks.max_amount | Largest amount of NEONs of the network to distribute with a single request
+| **solana**.networks.per_time_max_amount | Largest amount of NEONs of the network per time slice
+| **solana**.networks.time_slice_secs | Duration of the time slice of the network in seconds
+| **solana**.networks.evm_confirmations | Number of blocks to wait after an EVM transaction of the network is mined (**web3**.confirmations if missing)
+| **solana**.spl_tokens | List of SPL tokens sent to Solana wallets from associated token accounts of the operator
+| **solana**.spl_tokens.mint | Mint of the SPL token (`NEON` for wrapped NEON of the default network)
+| **solana**.spl_tokens.symbol | Symbol to request the SPL token by (optional)
+| **solana**.spl_tokens.max_amount | Largest amount of the SPL token to distribute with a single request
+| **solana**.spl_tokens.per_time_max_amount | Largest amount of the SPL token per time slice of the default network
+| **solana**.sol_mode | SOL source: `auto` (default; `requestAirdrop` RPC, operator if it's rejected), `airdrop` or `transfer` (from the operator)
+| **solana**.sol_max_amount | Largest amount of SOL to distribute with a single request (0, default, disables SOL airdrops)
+| **solana**.sol_per_time_max_amount | Largest amount of SOL per time slice of the default network
 |-
 
 Example of the configuration file contents:
@@ -102,6 +300,15 @@ private_key = "0x0000000000000000000000000000000000000000000000000000000000000Ac
 tokens = ["0x00000000000000000000000000000000CafeBabe",
           "0x00000000000000000000000000000000DeadBeef"]
 max_amount = 1000
+time_slice_secs = 3600
+
+[[web3.nfts]]
+address = "0x000000000000000000000000000000000000F00d"
+standard = "erc1155"
+mode = "mint"
+token_ids = [1, 2]
+max_amount = 5
+per_wallet_max_amount = 10
 
 [solana]
 enable = true
@@ -110,6 +317,24 @@ commitment = "processed"
 evm_loader = "EvmLoaderId11111111111111111111111111111111"
 operator_keyfile = "operator_id.json"
 max_amount = 10
+per_time_max_amount = 20
+time_slice_secs = 10
+sol_max_amount = 5
+sol_per_time_max_amount = 50
+
+[[solana.networks]]
+name = "second"
+evm_loader = "EvmLoaderId22222222222222222222222222222222"
+max_amount = 100
+per_time_max_amount = 200
+time_slice_secs = 10
+evm_confirmations = 2
+
+[[solana.spl_tokens]]
+mint = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+symbol = "USDC"
+max_amount = 100
+per_time_max_amount = 1000
 ```
 
 The configuration file is optional and, if present, can be incomplete
@@ -127,35 +352,73 @@ Environment variables, if present, override portions of the configuration.
 | FAUCET_RPC_BIND | **rpc**.bind | `0.0.0.0`
 | FAUCET_RPC_PORT | **rpc**.port | `3333`
 | FAUCET_RPC_ALLOWED_ORIGINS | **rpc**.allowed_origins | `["http://localhost"]`
+| FAUCET_RPC_GRPC_PORT | **rpc**.grpc_port | `3334`
+| FAUCET_RPC_EVENTS_TOKEN | **rpc**.events_token | `secret`
+| FAUCET_RPC_ADMIN_TOKEN | **rpc**.admin_token | `secret`
 | FAUCET_WEB3_ENABLE | **web3**.enable | `true`
 | WEB3_RPC_URL | **web3**.rpc_url | `http://localhost:9090/solana`
+| WEB3_WS_URL | **web3**.ws_url | `ws://localhost:9091`
+| WEB3_REQUEST_TIMEOUT_SECS | **web3**.request_timeout_secs | `10`
 | WEB3_PRIVATE_KEY | **web3**.private_key | `0x00A`
 | NEON_ERC20_TOKENS | **web3**.tokens | `["0x00B", "0x00C"]`
+| NEON_ERC20_TOKEN_ALIASES | **web3**.token_aliases | `["USDT=0x00B", "WETH=0x00C"]`
+| NEON_ERC20_TOKEN_MODES | **web3**.token_modes | `["0x00B=mint", "0x00C=auto"]`
 | NEON_ERC20_MAX_AMOUNT | **web3**.max_amount | `1000`
+| NEON_ERC20_PER_TIME_MAX_AMOUNT | **web3**.per_time_max_amount | `10000`
+| NEON_ERC20_TIME_SLICE_SECS | **web3**.time_slice_secs | `3600`
+| NEON_ERC20_TOKENS_REFRESH_SECS | **web3**.tokens_refresh_secs | `300`
+| WEB3_EIP1559 | **web3**.eip1559 | `true`
+| WEB3_MAX_FEE_PER_GAS | **web3**.max_fee_per_gas | `100000000000`
+| WEB3_MAX_PRIORITY_FEE_PER_GAS | **web3**.max_priority_fee_per_gas | `2000000000`
+| WEB3_GAS_MULTIPLIER | **web3**.gas_multiplier | `1.5`
+| WEB3_CONFIRMATIONS | **web3**.confirmations | `2`
+| WEB3_STUCK_TIMEOUT_SECS | **web3**.stuck_timeout_secs | `120`
+| WEB3_MAX_REPLACEMENTS | **web3**.max_replacements | `3`
+| WEB3_CANCEL_STUCK | **web3**.cancel_stuck | `true`
+| WEB3_FEE_BUMP_PERCENT | **web3**.fee_bump_percent | `20`
+| WEB3_BATCH_CONTRACT | **web3**.batch_contract | `0x00D`
+| WEB3_BATCH_WINDOW_MS | **web3**.batch_window_ms | `500`
 | FAUCET_SOLANA_ENABLE | **solana**.enable | `true`
 | SOLANA_URL | **solana**.url | `http://localhost:8899`
 | SOLANA_COMMITMENT | **solana**.commitment | `processed`
+| SOLANA_PREFLIGHT_COMMITMENT | **solana**.preflight_commitment | `processed`
+| SOLANA_CONFIRM_COMMITMENT | **solana**.confirm_commitment | `confirmed`
+| SOLANA_FIRE_AND_FORGET | **solana**.fire_and_forget | `true`
+| SOLANA_SIMULATE | **solana**.simulate | `true`
+| SOLANA_DURABLE_NONCE | **solana**.durable_nonce | `true`
+| SOLANA_NONCE_ACCOUNTS | **solana**.nonce_accounts | `["NonceAccount1111111111111111111111111111111"]`
+| SOLANA_NONCE_ACCOUNTS_COUNT | **solana**.nonce_accounts_count | `4`
 | EVM_LOADER | **solana**.evm_loader | `EvmLoaderId11111111111111111111111111111111`
+| NEON_BACKEND | **solana**.neon_backend | `evm`
 | NEON_OPERATOR_KEYFILE | **solana**.operator_keyfile | `operator_id.json`
 | NEON_ETH_MAX_AMOUNT | **solana**.max_amount | `10`
+| SOLANA_SOL_MODE | **solana**.sol_mode | `transfer`
+| SOLANA_SOL_MAX_AMOUNT | **solana**.sol_max_amount | `5`
+| SOLANA_SOL_PER_TIME_MAX_AMOUNT | **solana**.sol_per_time_max_amount | `50`
 | NEON_LOG | | `json`
 | RUST_LOG | | `info`
 |-
 "##;
 
+/// Renders the API manual with tables of the endpoints served by the server.
+fn api_manual() -> String {
+    let endpoints = crate::api::manual(&crate::server::endpoints());
+    format!("{}{}{}", MANUAL_API, endpoints, MANUAL_API_NOTES)
+}
+
 /// Dump manual in raw Markdown format.
 pub fn dump(api: bool, config: bool, env: bool) {
     println!("{}", MANUAL_HEADER);
 
     let all = !api && !config && !env;
     if all {
-        println!("{}", MANUAL_API);
+        println!("{}", api_manual());
         println!("{}", MANUAL_CONFIG);
         println!("{}", MANUAL_ENV);
     }
 
     if api {
-        println!("{}", MANUAL_API);
+        println!("{}", api_manual());
     }
     if config {
         println!("{}", MANUAL_CONFIG);
@@ -177,13 +440,13 @@ pub fn show(api: bool, config: bool, env: bool) {
 
     let all = !api && !config && !env;
     if all {
-        skin.print_text(MANUAL_API);
+        skin.print_text(&api_manual());
         skin.print_text(MANUAL_CONFIG);
         skin.print_text(MANUAL_ENV);
     }
 
     if api {
-        skin.print_text(MANUAL_API);
+        skin.print_text(&api_manual());
     }
     if config {
         skin.print_text(MANUAL_CONFIG);
@@ -9,39 +9,81 @@ use eyre::{eyre, Result};
 use forwarded_header_value::ForwardedHeaderValue;
 use tracing::{error, info};
 
-use crate::{config, ethereum, id::ReqId, solana};
+use crate::{api, config, eth_nonce, ethereum, id::ReqId, solana, web3_client};
 
 /// Represents packet of information needed for single airdrop operation.
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct Airdrop {
     /// Ethereum address of the recipient.
-    wallet: String,
+    pub wallet: String,
     /// Amount of a token to be received.
-    amount: u64,
+    pub amount: u64,
     /// Specifies amount in whole tokens (false, default) or in 10E-9 fractions (true).
     #[serde(default)]
     pub in_fractions: bool,
+    /// Name of the Neon EVM network; the default network if empty.
+    #[serde(default)]
+    pub network: String,
+    /// Commitment level to wait for; overrides the fire-and-forget mode.
+    #[serde(default)]
+    pub commitment: Option<String>,
+}
+
+impl api::Validate for Airdrop {
+    fn validate(&self) -> Result<(), String> {
+        ethereum::address_from_str(&self.wallet)
+            .map_err(|e| format!("Invalid wallet '{}': {}", self.wallet, e))?;
+        if self.amount == 0 {
+            return Err("Amount must be positive".into());
+        }
+        if let Some(commitment) = &self.commitment {
+            if config::parse_commitment(commitment).is_none() {
+                return Err(format!("Invalid commitment '{}'", commitment));
+            }
+        }
+        Ok(())
+    }
 }
 
-/// Processes the airdrop: sends needed transactions into Solana.
-pub async fn airdrop(id: &ReqId, params: Airdrop) -> Result<()> {
+/// Processes the airdrop: sends needed transactions into Solana
+/// (or into Neon EVM if `solana.neon_backend` is `evm`).
+/// Returns the signature of the Solana transaction or the hash of the EVM one.
+pub async fn airdrop(id: &ReqId, params: &Airdrop) -> Result<String> {
     info!("{} Processing NEON {:?}...", id, params);
 
-    if config::solana_account_seed_version() == 0 {
-        config::load_neon_params().await?;
-        check_token_account(id).await?;
+    if config::solana_evm_backend() {
+        let recipient = ethereum::address_from_str(&params.wallet)
+            .map_err(|e| eyre!("ethereum::address_from_str({}): {:?}", &params.wallet, e))?;
+        let hash = transfer(id, params, recipient)
+            .await
+            .map_err(|e| eyre!("neon_token::transfer({}): {:?}", recipient, e))?;
+        return Ok(format!("{:?}", hash));
+    }
+
+    let network = &params.network;
+    if config::solana_account_seed_version(network) == 0 {
+        config::load_neon_params(network).await?;
+        check_token_account(id, network).await?;
     }
 
     let operator = config::solana_operator_keypair()
         .map_err(|e| eyre!("config::solana_operator_keypair: {:?}", e))?;
     let ether_address = ethereum::address_from_str(&params.wallet)
         .map_err(|e| eyre!("ethereum::address_from_str({}): {:?}", &params.wallet, e))?;
-    solana::deposit_token(
+    let commitment = match params.commitment {
+        Some(ref c) => {
+            Some(config::parse_commitment(c).ok_or_else(|| eyre!("Invalid commitment '{}'", c))?)
+        }
+        None => None,
+    };
+    let signature = solana::deposit_token(
         id,
+        network,
         operator,
         ether_address,
         params.amount,
         params.in_fractions,
+        commitment,
     )
     .await
     .map_err(|e| {
@@ -51,11 +93,99 @@ pub async fn airdrop(id: &ReqId, params: Airdrop) -> Result<()> {
             e
         )
     })?;
-    Ok(())
+    Ok(signature.to_string())
+}
+
+/// Number of decimals of NEON in Neon EVM (like ETH).
+const NEON_EVM_DECIMALS: u32 = 18;
+
+/// Number of decimals of galans (10E-9 NEON), fractions of NEON in the `evm` backend.
+const GALAN_DECIMALS: u32 = 9;
+
+/// Converts whole NEON into fractions: galans in the `evm` backend
+/// or fractions of the token mint of the `network` otherwise.
+pub fn convert_whole_to_fractions(network: &str, amount: u64) -> Result<u64> {
+    if !config::solana_evm_backend() {
+        return solana::convert_whole_to_fractions(network, amount);
+    }
+    amount
+        .checked_mul(10_u64.pow(GALAN_DECIMALS))
+        .ok_or_else(|| eyre!("Overflow {}*10^{}", amount, GALAN_DECIMALS))
 }
 
-/// Checks existence and balance of the operator's token account.
-async fn check_token_account(id: &ReqId) -> Result<()> {
+/// Sends NEON as a plain EVM value transfer from the `web3.private_key` account.
+async fn transfer(
+    id: &ReqId,
+    params: &Airdrop,
+    recipient: ethereum::Address,
+) -> Result<web3::types::H256> {
+    use secp256k1::SecretKey;
+    use web3::signing::{Key as _, SecretKeyRef};
+    use web3::types::{CallRequest, TransactionParameters, U64};
+
+    let galans = if params.in_fractions {
+        params.amount
+    } else {
+        convert_whole_to_fractions(&params.network, params.amount)?
+    };
+    let value = galans_to_wei(galans);
+
+    let admin_key: SecretKey = config::web3_private_key().parse()?;
+    let sender = SecretKeyRef::new(&admin_key).address();
+    let web3 = web3_client::get()?;
+
+    info!("{} Transfer {} wei of NEON -> {}", id, value, recipient);
+    let call = CallRequest {
+        from: Some(sender),
+        to: Some(recipient),
+        value: Some(value),
+        ..CallRequest::default()
+    };
+    let gas = web3.eth().estimate_gas(call, None).await?;
+    let tx = TransactionParameters {
+        to: Some(recipient),
+        value,
+        gas,
+        ..TransactionParameters::default()
+    };
+    let confirmations = config::solana_evm_confirmations(&params.network);
+    let receipt = eth_nonce::send(id, &web3, tx, &admin_key, confirmations).await?;
+    if receipt.status != Some(U64::from(1)) {
+        return Err(eyre!(
+            "Transaction {:?} failed with status {:?}",
+            receipt.transaction_hash,
+            receipt.status
+        ));
+    }
+
+    info!("{} OK {:?}", id, receipt.transaction_hash);
+    Ok(receipt.transaction_hash)
+}
+
+/// Converts amount of galans into wei (10E-18).
+fn galans_to_wei(galans: u64) -> web3::types::U256 {
+    // u64 * 10^9 always fits into U256
+    web3::types::U256::from(galans)
+        * web3::types::U256::exp10((NEON_EVM_DECIMALS - GALAN_DECIMALS) as usize)
+}
+
+#[test]
+fn test_galans_to_wei() {
+    use web3::types::U256;
+    assert_eq!(galans_to_wei(0), U256::zero());
+    assert_eq!(galans_to_wei(1), U256::from(1_000_000_000_u64));
+    assert_eq!(
+        galans_to_wei(5_000_000_000),
+        U256::from(5_000_000_000_000_000_000_u128)
+    );
+    assert_eq!(
+        galans_to_wei(u64::MAX),
+        U256::from(u64::MAX) * U256::from(1_000_000_000_u64)
+    );
+}
+
+/// Checks existence and balance of the operator's token account in the `network`.
+async fn check_token_account(id: &ReqId, network: &str) -> Result<()> {
     use eyre::WrapErr as _;
     use solana_account_decoder::parse_token::UiTokenAmount;
     use solana_client::client_error::Result as ClientResult;
@@ -68,12 +198,14 @@ async fn check_token_account(id: &ReqId) -> Result<()> {
         .map_err(|e| eyre!("config::solana_operator_keypair: {:?}", e))?;
     let operator_pubkey = operator.pubkey();
 
-    let token_mint_id = Pubkey::from_str(&config::solana_token_mint_id()).wrap_err_with(|| {
-        eyre!(
-            "config::solana_token_mint_id returns {}",
-            &config::solana_token_mint_id(),
-        )
-    })?;
+    let token_mint_id =
+        Pubkey::from_str(&config::solana_token_mint_id(network)).wrap_err_with(|| {
+            eyre!(
+                "config::solana_token_mint_id({}) returns {}",
+                network,
+                &config::solana_token_mint_id(network),
+            )
+        })?;
 
     let operator_token_pubkey = spl_associated_token_account::get_associated_token_address(
         &operator_pubkey,
@@ -131,6 +263,52 @@ impl std::fmt::Display for AirdropLimiterError {
     }
 }
 
+/// Represents a source of airdrop requests accounted by the limiter.
+pub trait Requester {
+    /// Returns the address of the immediate peer.
+    fn peer_addr(&self) -> Option<IpAddr>;
+    /// Returns the forwarded header value (`Forwarded`, then `X-Forwarded-For`) if present.
+    fn forwarded(&self) -> Result<Option<ForwardedHeaderValue>, AirdropLimiterError>;
+}
+
+impl Requester for HttpRequest {
+    fn peer_addr(&self) -> Option<IpAddr> {
+        HttpRequest::peer_addr(self).map(|socket| socket.ip())
+    }
+
+    // See https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Forwarded
+    fn forwarded(&self) -> Result<Option<ForwardedHeaderValue>, AirdropLimiterError> {
+        if let Some(forwarded) = self.headers().get(http::header::FORWARDED) {
+            let forwarded = forwarded
+                .to_str()
+                .map_err(|_| AirdropLimiterError::BadRequest)?;
+            return ForwardedHeaderValue::from_str(forwarded)
+                .map(Some)
+                .map_err(|_| AirdropLimiterError::BadRequest);
+        }
+        if let Some(forwarded) = self.headers().get("X-Forwarded-For") {
+            let forwarded = forwarded
+                .to_str()
+                .map_err(|_| AirdropLimiterError::BadRequest)?;
+            return ForwardedHeaderValue::from_x_forwarded_for(forwarded)
+                .map(Some)
+                .map_err(|_| AirdropLimiterError::BadRequest);
+        }
+        Ok(None)
+    }
+}
+
+/// Requests coming without forwarding headers, like gRPC calls.
+impl Requester for IpAddr {
+    fn peer_addr(&self) -> Option<IpAddr> {
+        Some(*self)
+    }
+
+    fn forwarded(&self) -> Result<Option<ForwardedHeaderValue>, AirdropLimiterError> {
+        Ok(None)
+    }
+}
+
 pub struct AirdropLimiter {
     trusted_proxies: HashSet<IpAddr>,
     blacklist: HashSet<IpAddr>,
@@ -160,13 +338,48 @@ impl AirdropLimiter {
         self.ip_cache.clear();
     }
 
+    /// Returns a limiter of another token with the same trusted proxies and blacklist.
+    pub fn with_caps(&self, per_request_cap: u64, per_time_cap: u64) -> Self {
+        Self::new(
+            self.trusted_proxies.clone(),
+            self.blacklist.clone(),
+            per_request_cap,
+            per_time_cap,
+        )
+    }
+
     pub fn check_cache(
         &mut self,
-        req: &HttpRequest,
+        req: &impl Requester,
         airdrop: &Airdrop
     ) -> Result<(), AirdropLimiterError> {
-        let peer = self.get_peer(req)?;
         let request_amount = Self::parse_amount(airdrop)?;
+        self.check_amount(req, request_amount)
+    }
+
+    /// Returns the amount of the airdrop which won't be sent back to the peer's limit.
+    pub fn release_cache(&mut self, req: &impl Requester, airdrop: &Airdrop) {
+        if let Ok(amount) = Self::parse_amount(airdrop) {
+            self.release_amount(req, amount);
+        }
+    }
+
+    /// Returns `amount` of token fractions which won't be sent back to the peer's limit.
+    pub fn release_amount(&mut self, req: &impl Requester, amount: u64) {
+        if let Ok(peer) = self.get_peer(req) {
+            if let Some(total) = self.ip_cache.get_mut(&peer) {
+                *total = total.saturating_sub(amount);
+            }
+        }
+    }
+
+    /// Checks and accounts `request_amount` of token fractions requested by the peer.
+    pub fn check_amount(
+        &mut self,
+        req: &impl Requester,
+        request_amount: u64,
+    ) -> Result<(), AirdropLimiterError> {
+        let peer = self.get_peer(req)?;
         if request_amount > self.per_request_cap {
             error!("Airdrop request capped at {}", self.per_request_cap);
             return Err(AirdropLimiterError::CapExceeded(AirdropCapExceeded {
@@ -188,10 +401,9 @@ impl AirdropLimiter {
         Ok(())
     }
 
-    fn get_peer(&self, req: &HttpRequest) -> Result<IpAddr, AirdropLimiterError> {
+    fn get_peer(&self, req: &impl Requester) -> Result<IpAddr, AirdropLimiterError> {
         let peer = req
             .peer_addr()
-            .map(|socket| socket.ip())
             .ok_or_else(|| AirdropLimiterError::BadRequest)?;
         if !self.trusted_proxies.contains(&peer) {
             if self.blacklist.contains(&peer) {
@@ -203,27 +415,9 @@ impl AirdropLimiter {
             return Ok(peer);
         }
         // If the peer is our known proxy, then we trust the forwarded headers if they exist.
-        // See https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Forwarded
-        let forwarded = match req.headers().get(http::header::FORWARDED) {
-            Some(forwarded) => {
-                let forwarded = forwarded
-                    .to_str()
-                    .map_err(|_| AirdropLimiterError::BadRequest)?;
-                ForwardedHeaderValue::from_str(forwarded)
-                    .map_err(|_| AirdropLimiterError::BadRequest)?
-            },
-            None => match req.headers().get("X-Forwarded-For") {
-                Some(forwarded) => {
-                    let forwarded = forwarded
-                        .to_str()
-                        .map_err(|_| AirdropLimiterError::BadRequest)?;
-                    ForwardedHeaderValue::from_x_forwarded_for(forwarded)
-                        .map_err(|_| AirdropLimiterError::BadRequest)?
-                },
-                None => {
-                    return Ok(peer)
-                },
-            }
+        let forwarded = match req.forwarded()? {
+            Some(forwarded) => forwarded,
+            None => return Ok(peer),
         };
         let peer = forwarded
             .proximate_forwarded_for_ip()
@@ -241,7 +435,7 @@ impl AirdropLimiter {
         let request_amount = if airdrop.in_fractions {
             airdrop.amount
         } else {
-            solana::convert_whole_to_fractions(airdrop.amount)
+            convert_whole_to_fractions(&airdrop.network, airdrop.amount)
                 .map_err(|_| AirdropLimiterError::BadConversion)?
         };
         Ok(request_amount)
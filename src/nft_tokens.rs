@@ -0,0 +1,622 @@
+//! Faucet NFT (ERC721/ERC1155) test assets module.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use eyre::{eyre, Result};
+use futures_locks::RwLock;
+use tracing::{info, warn};
+
+use secp256k1::SecretKey;
+use web3::contract::{Contract, Options};
+use web3::signing::{keccak256, Key as _, SecretKeyRef};
+use web3::transports::Http;
+use web3::types::{H256, U256};
+use web3::Transport;
+
+use crate::web3_client::{self, Abi};
+use crate::{api, config, erc20_tokens, ethereum, id::ReqId};
+
+/// Represents packet of information needed for an NFT airdrop operation.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct Airdrop {
+    /// Ethereum address of the recipient.
+    pub wallet: String,
+    /// Address of the NFT collection.
+    pub contract: String,
+    /// Token id of an ERC1155 collection. If missing, the first configured one.
+    pub token_id: Option<u64>,
+    /// Amount of ERC1155 tokens to be received (1 by default).
+    pub amount: Option<u64>,
+}
+
+impl api::Validate for Airdrop {
+    fn validate(&self) -> Result<(), String> {
+        ethereum::address_from_str(&self.wallet)
+            .map_err(|e| format!("Invalid wallet '{}': {}", self.wallet, e))?;
+        ethereum::address_from_str(&self.contract)
+            .map_err(|e| format!("Invalid contract '{}': {}", self.contract, e))?;
+        if self.amount == Some(0) {
+            return Err("Amount must be positive".into());
+        }
+        Ok(())
+    }
+}
+
+/// Represents an error of an NFT request which can't be served.
+#[derive(thiserror::Error, Debug)]
+#[error("{0}")]
+pub struct InvalidRequest(String);
+
+/// Represents an error of a request exceeding `web3.nfts.per_wallet_max_amount`.
+#[derive(thiserror::Error, Debug)]
+#[error("Requested total {total} of NFT {collection} exceeds the per wallet limit {cap}")]
+pub struct PerWalletCapExceeded {
+    collection: String,
+    total: u64,
+    cap: u64,
+}
+
+/// Represents an NFT airdrop accounted in the per wallet cap.
+#[derive(Debug)]
+pub struct Prepared {
+    pub collection: String,
+    standard: Standard,
+    recipient: ethereum::Address,
+    token_id: u64,
+    pub amount: u64,
+    /// Largest amount of tokens per request.
+    pub max_amount: u64,
+    /// Largest amount of tokens per wallet (and per client) in a time slice.
+    pub per_time_max_amount: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Standard {
+    Erc721,
+    Erc1155,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    Mint,
+    Inventory,
+}
+
+/// Represents an entry of the list of available NFT collections.
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct CollectionInfo {
+    address: String,
+    standard: String,
+    mode: String,
+    max_amount: u64,
+    /// Largest amount of tokens per wallet in a time slice.
+    per_wallet_max_amount: u64,
+    /// Tokens owned by the faucet; empty in the mint mode.
+    inventory: Vec<InventoryItem>,
+}
+
+#[derive(Debug, serde::Serialize, schemars::JsonSchema)]
+pub struct InventoryItem {
+    token_id: u64,
+    amount: String,
+}
+
+/// Amounts of tokens dispensed to wallets by collections in the current time slice.
+struct Dispensed {
+    since: Instant,
+    amounts: HashMap<(String, ethereum::Address), u64>,
+}
+
+impl Default for Dispensed {
+    fn default() -> Self {
+        Self {
+            since: Instant::now(),
+            amounts: Default::default(),
+        }
+    }
+}
+
+impl Dispensed {
+    /// Accounts the amount of the wallet unless the total exceeds the `cap`.
+    fn reserve(
+        &mut self,
+        collection: &str,
+        recipient: ethereum::Address,
+        amount: u64,
+        cap: u64,
+    ) -> Result<(), PerWalletCapExceeded> {
+        let total = self
+            .amounts
+            .entry((collection.to_string(), recipient))
+            .or_default();
+        let requested = total.saturating_add(amount);
+        if requested > cap {
+            return Err(PerWalletCapExceeded {
+                collection: collection.to_string(),
+                total: requested,
+                cap,
+            });
+        }
+        *total = requested;
+        Ok(())
+    }
+
+    /// Returns the amount which won't be dispensed back to the wallet's cap.
+    fn release(&mut self, collection: &str, recipient: ethereum::Address, amount: u64) {
+        if let Some(total) = self.amounts.get_mut(&(collection.to_string(), recipient)) {
+            *total = total.saturating_sub(amount);
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref DISPENSED: RwLock<Dispensed> = RwLock::new(Dispensed::default());
+    /// ERC721 tokens being transferred from the inventory.
+    static ref RESERVED: RwLock<HashSet<(String, u64)>> = RwLock::new(HashSet::new());
+}
+
+/// Validates the NFT airdrop and accounts it in the per wallet cap.
+pub async fn prepare(id: &ReqId, params: &Airdrop) -> Result<Prepared> {
+    info!("{} Processing NFT {:?}...", id, params);
+
+    let collection = find_collection(&params.contract, &config::web3_nfts())?;
+    let standard = parse_standard(&collection)?;
+    let recipient = ethereum::address_from_str(&params.wallet)?;
+
+    let amount = params.amount.unwrap_or(1);
+    let max_amount = match standard {
+        Standard::Erc721 => 1,
+        Standard::Erc1155 => config::web3_nft_max_amount(&collection),
+    };
+    check_amount(&collection, amount, max_amount)?;
+    let token_id = select_token_id(
+        &collection,
+        standard,
+        params.token_id,
+        &config::web3_nft_token_ids(&collection),
+    )?;
+
+    let per_time_max_amount = config::web3_nft_per_wallet_max_amount(&collection);
+    reserve_per_wallet(&collection, recipient, amount, per_time_max_amount).await?;
+    Ok(Prepared {
+        collection,
+        standard,
+        recipient,
+        token_id,
+        amount,
+        max_amount,
+        per_time_max_amount,
+    })
+}
+
+/// Mints or transfers tokens of the prepared airdrop to the recipient, returns the transaction hash.
+/// The per wallet cap of a failed airdrop is released.
+pub async fn send(id: &ReqId, prepared: Prepared) -> Result<H256> {
+    let result = dispense(
+        id,
+        &prepared.collection,
+        prepared.standard,
+        prepared.recipient,
+        prepared.token_id,
+        prepared.amount,
+    )
+    .await;
+    if result.is_err() {
+        cancel(prepared).await;
+    }
+    result
+}
+
+/// Releases the per wallet cap accounted for an airdrop which won't be sent.
+pub async fn cancel(prepared: Prepared) {
+    DISPENSED
+        .write()
+        .await
+        .release(&prepared.collection, prepared.recipient, prepared.amount);
+}
+
+/// Returns available NFT collections with their caps and inventory of the faucet.
+pub async fn list(id: &ReqId) -> Result<Vec<CollectionInfo>> {
+    let admin_key: SecretKey = config::web3_private_key().parse()?;
+
+    let mut list = Vec::new();
+    for collection in config::web3_nfts() {
+        let standard = parse_standard(&collection)?;
+        let contract = load_contract(&collection, standard)?;
+        let mode = dispensing_mode(id, &contract, &collection, &admin_key).await;
+        let inventory = match mode {
+            Mode::Mint => Vec::new(),
+            Mode::Inventory => load_inventory(&contract, &collection, standard, &admin_key).await?,
+        };
+        list.push(CollectionInfo {
+            standard: config::web3_nft_standard(&collection),
+            mode: match mode {
+                Mode::Mint => config::NFT_MODE_MINT.into(),
+                Mode::Inventory => config::NFT_MODE_INVENTORY.into(),
+            },
+            max_amount: match standard {
+                Standard::Erc721 => 1,
+                Standard::Erc1155 => config::web3_nft_max_amount(&collection),
+            },
+            per_wallet_max_amount: config::web3_nft_per_wallet_max_amount(&collection),
+            inventory,
+            address: collection,
+        });
+    }
+
+    Ok(list)
+}
+
+/// Mints or transfers tokens of the collection to the recipient.
+async fn dispense(
+    id: &ReqId,
+    collection: &str,
+    standard: Standard,
+    recipient: ethereum::Address,
+    token_id: u64,
+    amount: u64,
+) -> Result<H256> {
+    let web3 = web3_client::get()?;
+    let admin_key: SecretKey = config::web3_private_key().parse()?;
+    let owner = SecretKeyRef::new(&admin_key).address();
+    let contract = load_contract(collection, standard)?;
+
+    let receipt = match (
+        standard,
+        dispensing_mode(id, &contract, collection, &admin_key).await,
+    ) {
+        (Standard::Erc721, Mode::Mint) => {
+            info!("{} Mint NFT {} -> {}", id, collection, recipient);
+            erc20_tokens::send_call(id, &web3, &contract, "mint", recipient, &admin_key).await?
+        }
+        (Standard::Erc721, Mode::Inventory) => {
+            let token_id = reserve_inventory(&contract, collection, owner).await?;
+            info!(
+                "{} Transfer NFT {} #{} -> {}",
+                id, collection, token_id, recipient
+            );
+            let result = erc20_tokens::send_call(
+                id,
+                &web3,
+                &contract,
+                "safeTransferFrom",
+                (owner, recipient, U256::from(token_id)),
+                &admin_key,
+            )
+            .await;
+            RESERVED
+                .write()
+                .await
+                .remove(&(collection.to_string(), token_id));
+            result?
+        }
+        (Standard::Erc1155, Mode::Mint) => {
+            info!(
+                "{} Mint {} of NFT {} #{} -> {}",
+                id, amount, collection, token_id, recipient
+            );
+            erc20_tokens::send_call(
+                id,
+                &web3,
+                &contract,
+                "mint",
+                (
+                    recipient,
+                    U256::from(token_id),
+                    U256::from(amount),
+                    Vec::<u8>::new(),
+                ),
+                &admin_key,
+            )
+            .await?
+        }
+        (Standard::Erc1155, Mode::Inventory) => {
+            let balance: U256 = contract
+                .query(
+                    "balanceOf",
+                    (owner, U256::from(token_id)),
+                    None,
+                    Options::default(),
+                    None,
+                )
+                .await?;
+            if balance < U256::from(amount) {
+                return Err(eyre!(
+                    "NFT {} #{} is out of inventory: {} left",
+                    collection,
+                    token_id,
+                    balance
+                ));
+            }
+            info!(
+                "{} Transfer {} of NFT {} #{} -> {}",
+                id, amount, collection, token_id, recipient
+            );
+            erc20_tokens::send_call(
+                id,
+                &web3,
+                &contract,
+                "safeTransferFrom",
+                (
+                    owner,
+                    recipient,
+                    U256::from(token_id),
+                    U256::from(amount),
+                    Vec::<u8>::new(),
+                ),
+                &admin_key,
+            )
+            .await?
+        }
+    };
+
+    Ok(receipt.transaction_hash)
+}
+
+/// Finds the collection by address among `collections`; the comparison is case-insensitive.
+fn find_collection(address: &str, collections: &[String]) -> Result<String> {
+    let requested = ethereum::address_from_str(address).ok();
+    collections
+        .iter()
+        .find(|c| requested.is_some() && ethereum::address_from_str(c).ok() == requested)
+        .cloned()
+        .ok_or_else(|| {
+            InvalidRequest(format!(
+                "Unknown NFT '{}'; valid options: {}",
+                address,
+                collections.join(", ")
+            ))
+            .into()
+        })
+}
+
+/// Checks the requested amount against the range 1..=`max_amount`.
+fn check_amount(collection: &str, amount: u64, max_amount: u64) -> Result<()> {
+    if amount == 0 || amount > max_amount {
+        return Err(InvalidRequest(format!(
+            "Requested amount {} of NFT {} is out of range 1..={}",
+            amount, collection, max_amount
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Returns the requested token id or the first of `token_ids`.
+/// Requested ERC1155 token ids must be among `token_ids`.
+fn select_token_id(
+    collection: &str,
+    standard: Standard,
+    requested: Option<u64>,
+    token_ids: &[u64],
+) -> Result<u64> {
+    match requested {
+        Some(token_id) if standard == Standard::Erc1155 && !token_ids.contains(&token_id) => {
+            Err(InvalidRequest(format!(
+                "Unknown token id {} of NFT {}; valid options: {:?}",
+                token_id, collection, token_ids
+            ))
+            .into())
+        }
+        Some(token_id) => Ok(token_id),
+        None => Ok(token_ids.first().copied().unwrap_or_default()),
+    }
+}
+
+/// Returns the standard of the collection.
+fn parse_standard(collection: &str) -> Result<Standard> {
+    match config::web3_nft_standard(collection).as_str() {
+        config::NFT_ERC721 => Ok(Standard::Erc721),
+        config::NFT_ERC1155 => Ok(Standard::Erc1155),
+        s => Err(eyre!("Unknown standard '{}' of NFT {}", s, collection)),
+    }
+}
+
+/// Returns the dispensing mode of the collection.
+/// In the auto mode tokens are minted if the faucet has the minter role.
+async fn dispensing_mode<T: Transport>(
+    id: &ReqId,
+    contract: &Contract<T>,
+    collection: &str,
+    admin_key: &SecretKey,
+) -> Mode {
+    match config::web3_nft_mode(collection).as_str() {
+        config::NFT_MODE_MINT => return Mode::Mint,
+        config::NFT_MODE_INVENTORY => return Mode::Inventory,
+        _ => {}
+    }
+
+    let minter_role = H256::from(keccak256(b"MINTER_ROLE"));
+    let owner = SecretKeyRef::new(admin_key).address();
+    let is_minter: Result<bool, _> = contract
+        .query(
+            "hasRole",
+            (minter_role, owner),
+            None,
+            Options::default(),
+            None,
+        )
+        .await;
+    match is_minter {
+        Ok(true) => Mode::Mint,
+        Ok(false) => Mode::Inventory,
+        Err(e) => {
+            warn!("{} NFT {} has no roles: {}", id, collection, e);
+            Mode::Inventory
+        }
+    }
+}
+
+/// Returns the handle of the collection contract with the ABI of its standard.
+fn load_contract(collection: &str, standard: Standard) -> Result<Contract<Http>> {
+    let abi = match standard {
+        Standard::Erc721 => Abi::Erc721,
+        Standard::Erc1155 => Abi::Erc1155,
+    };
+    web3_client::contract(abi, ethereum::address_from_str(collection)?)
+}
+
+/// Returns configured tokens owned by the faucet.
+async fn load_inventory<T: Transport>(
+    contract: &Contract<T>,
+    collection: &str,
+    standard: Standard,
+    admin_key: &SecretKey,
+) -> Result<Vec<InventoryItem>> {
+    let owner = SecretKeyRef::new(admin_key).address();
+    let mut inventory = Vec::new();
+    for token_id in config::web3_nft_token_ids(collection) {
+        let amount = match standard {
+            Standard::Erc721 => {
+                let token_owner: ethereum::Address = contract
+                    .query(
+                        "ownerOf",
+                        U256::from(token_id),
+                        None,
+                        Options::default(),
+                        None,
+                    )
+                    .await?;
+                U256::from((token_owner == owner) as u64)
+            }
+            Standard::Erc1155 => {
+                contract
+                    .query(
+                        "balanceOf",
+                        (owner, U256::from(token_id)),
+                        None,
+                        Options::default(),
+                        None,
+                    )
+                    .await?
+            }
+        };
+        if !amount.is_zero() {
+            inventory.push(InventoryItem {
+                token_id,
+                amount: amount.to_string(),
+            });
+        }
+    }
+    Ok(inventory)
+}
+
+/// Picks a configured ERC721 token owned by the faucet and not being transferred.
+async fn reserve_inventory<T: Transport>(
+    contract: &Contract<T>,
+    collection: &str,
+    owner: ethereum::Address,
+) -> Result<u64> {
+    for token_id in config::web3_nft_token_ids(collection) {
+        let key = (collection.to_string(), token_id);
+        if RESERVED.read().await.contains(&key) {
+            continue;
+        }
+        let token_owner: ethereum::Address = contract
+            .query(
+                "ownerOf",
+                U256::from(token_id),
+                None,
+                Options::default(),
+                None,
+            )
+            .await?;
+        if token_owner == owner && RESERVED.write().await.insert(key) {
+            return Ok(token_id);
+        }
+    }
+    Err(eyre!("NFT {} is out of inventory", collection))
+}
+
+/// Checks and accounts the amount against `web3.nfts.per_wallet_max_amount`
+/// in the current time slice of `web3.time_slice_secs`.
+async fn reserve_per_wallet(
+    collection: &str,
+    recipient: ethereum::Address,
+    amount: u64,
+    cap: u64,
+) -> Result<()> {
+    let mut dispensed = DISPENSED.write().await;
+    let time_slice = Duration::from_secs(config::web3_time_slice_secs());
+    if dispensed.since.elapsed() >= time_slice {
+        *dispensed = Dispensed::default();
+    }
+    dispensed.reserve(collection, recipient, amount, cap)?;
+    Ok(())
+}
+
+#[test]
+fn test_dispensed() {
+    let first = ethereum::Address::from_low_u64_be(1);
+    let second = ethereum::Address::from_low_u64_be(2);
+    let mut dispensed = Dispensed::default();
+
+    assert!(dispensed.reserve("A", first, 2, 3).is_ok());
+    let err = dispensed.reserve("A", first, 2, 3).unwrap_err();
+    assert_eq!(err.total, 4);
+    assert_eq!(err.cap, 3);
+    assert!(dispensed.reserve("A", second, 3, 3).is_ok());
+    assert!(dispensed.reserve("B", first, 3, 3).is_ok());
+
+    dispensed.release("A", first, 2);
+    assert!(dispensed.reserve("A", first, 3, 3).is_ok());
+    dispensed.release("A", first, 5);
+    assert_eq!(dispensed.amounts[&("A".to_string(), first)], 0);
+    dispensed.release("C", first, 1);
+    assert!(!dispensed.amounts.contains_key(&("C".to_string(), first)));
+}
+
+#[test]
+fn test_find_collection() {
+    let collections = vec![
+        "0x000000000000000000000000000000000000F00d".to_string(),
+        "0x000000000000000000000000000000000000bEEF".to_string(),
+    ];
+    assert_eq!(
+        find_collection("0x000000000000000000000000000000000000f00d", &collections).unwrap(),
+        collections[0]
+    );
+    assert_eq!(
+        find_collection("0x000000000000000000000000000000000000BEEF", &collections).unwrap(),
+        collections[1]
+    );
+    let err =
+        find_collection("0x000000000000000000000000000000000000CafE", &collections).unwrap_err();
+    assert!(err.downcast_ref::<InvalidRequest>().is_some());
+    assert!(find_collection("F00d", &collections).is_err());
+    assert!(find_collection("0x000000000000000000000000000000000000F00d", &[]).is_err());
+}
+
+#[test]
+fn test_check_amount() {
+    assert!(check_amount("A", 1, 1).is_ok());
+    assert!(check_amount("A", 5, 5).is_ok());
+    let err = check_amount("A", 0, 5).unwrap_err();
+    assert!(err.downcast_ref::<InvalidRequest>().is_some());
+    let err = check_amount("A", 2, 1).unwrap_err();
+    assert!(err.downcast_ref::<InvalidRequest>().is_some());
+}
+
+#[test]
+fn test_select_token_id() {
+    let token_ids = [7, 9];
+    assert_eq!(
+        select_token_id("A", Standard::Erc1155, None, &token_ids).unwrap(),
+        7
+    );
+    assert_eq!(
+        select_token_id("A", Standard::Erc1155, Some(9), &token_ids).unwrap(),
+        9
+    );
+    let err = select_token_id("A", Standard::Erc1155, Some(8), &token_ids).unwrap_err();
+    assert!(err.downcast_ref::<InvalidRequest>().is_some());
+    assert_eq!(
+        select_token_id("A", Standard::Erc721, Some(8), &token_ids).unwrap(),
+        8
+    );
+    assert_eq!(
+        select_token_id("A", Standard::Erc721, None, &[]).unwrap(),
+        0
+    );
+}
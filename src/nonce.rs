@@ -0,0 +1,221 @@
+//! Faucet durable nonce module.
+
+use std::collections::HashMap;
+use std::str::FromStr as _;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use eyre::{eyre, Result, WrapErr as _};
+use futures_locks::RwLock;
+use tokio::sync::OwnedMutexGuard;
+use tracing::info;
+
+use solana_client::nonce_utils;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::nonce::State as NonceState;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer as _;
+use solana_sdk::signer::keypair::Keypair;
+use solana_sdk::system_instruction;
+use solana_sdk::system_program;
+use solana_sdk::transaction::Transaction;
+
+use crate::{config, id::ReqId, solana};
+
+/// Prefix of seeds of nonce accounts created by the faucet.
+const NONCE_SEED_PREFIX: &str = "faucet-nonce";
+
+/// Time a signed transaction is kept to be re-sent on retries of its request.
+const SIGNED_RETENTION: Duration = Duration::from_secs(600);
+
+lazy_static::lazy_static! {
+    static ref NONCE_ACCOUNTS: RwLock<Vec<Pubkey>> = RwLock::new(Vec::new());
+    static ref NEXT_NONCE_ACCOUNT: AtomicUsize = AtomicUsize::new(0);
+    static ref SIGNED: Mutex<HashMap<String, Signed>> = Mutex::new(HashMap::new());
+}
+
+/// Represents a durable nonce transaction signed for a request.
+struct Signed {
+    /// Recipient and amounts of the transaction; a retry of the request must match them.
+    purpose: String,
+    account: Pubkey,
+    tx: Transaction,
+    expires: Instant,
+}
+
+/// Takes a nonce account for exclusive use by a transaction of the `purpose`.
+/// A retried request takes the account of the transaction signed before.
+/// Returns `None` if durable nonces are disabled.
+pub async fn acquire(id: &ReqId, purpose: &str) -> Result<Option<(Pubkey, OwnedMutexGuard<()>)>> {
+    if !config::solana_durable_nonce() {
+        return Ok(None);
+    }
+
+    let account = match signed(id, purpose) {
+        Some((account, _)) => account,
+        None => {
+            let accounts = accounts(id).await?;
+            let i = NEXT_NONCE_ACCOUNT.fetch_add(1, Ordering::Relaxed) % accounts.len();
+            accounts[i]
+        }
+    };
+    let guard = solana::lock_account(account).await;
+    Ok(Some((account, guard)))
+}
+
+/// Remembers the transaction signed for the request, so a retry of the request
+/// (with the same client id) re-sends it instead of paying twice if the first response is lost.
+pub fn remember(id: &ReqId, purpose: &str, account: Pubkey, tx: &Transaction) {
+    let client = match id.client() {
+        Some(client) => client,
+        None => return,
+    };
+    let now = Instant::now();
+    let mut signed = SIGNED.lock().unwrap();
+    signed.retain(|_, s| s.expires > now);
+    signed.insert(
+        client.to_owned(),
+        Signed {
+            purpose: purpose.to_owned(),
+            account,
+            tx: tx.clone(),
+            expires: now + SIGNED_RETENTION,
+        },
+    );
+}
+
+/// Returns the nonce account and the transaction signed for the request of the same `purpose`.
+pub fn signed(id: &ReqId, purpose: &str) -> Option<(Pubkey, Transaction)> {
+    let signed = SIGNED.lock().unwrap();
+    signed
+        .get(id.client()?)
+        .filter(|s| s.purpose == purpose && s.expires > Instant::now())
+        .map(|s| (s.account, s.tx.clone()))
+}
+
+/// Forgets the transaction of the request which can't be confirmed anymore.
+pub fn forget(id: &ReqId) {
+    if let Some(client) = id.client() {
+        SIGNED.lock().unwrap().remove(client);
+    }
+}
+
+/// Returns nonce accounts, initializing them on first call.
+async fn accounts(id: &ReqId) -> Result<Vec<Pubkey>> {
+    {
+        let accounts = NONCE_ACCOUNTS.read().await;
+        if !accounts.is_empty() {
+            return Ok(accounts.clone());
+        }
+    }
+
+    let mut accounts = NONCE_ACCOUNTS.write().await;
+    if accounts.is_empty() {
+        let id = id.to_owned();
+        *accounts = tokio::task::spawn_blocking(move || -> Result<Vec<Pubkey>> {
+            let client =
+                RpcClient::new_with_commitment(config::solana_url(), config::solana_commitment());
+            let operator = config::solana_operator_keypair()
+                .map_err(|e| eyre!("config::solana_operator_keypair: {:?}", e))?;
+            init_accounts(&id, &client, &operator)
+        })
+        .await??;
+    }
+    Ok(accounts.clone())
+}
+
+/// Parses configured nonce accounts or creates missing seeded ones.
+fn init_accounts(id: &ReqId, client: &RpcClient, operator: &Keypair) -> Result<Vec<Pubkey>> {
+    let configured = config::solana_nonce_accounts();
+    if !configured.is_empty() {
+        return configured
+            .iter()
+            .map(|a| Pubkey::from_str(a).wrap_err_with(|| eyre!("Invalid nonce account {}", a)))
+            .collect();
+    }
+
+    let base = operator.pubkey();
+    let lamports = client.get_minimum_balance_for_rent_exemption(NonceState::size())?;
+    (0..config::solana_nonce_accounts_count().max(1))
+        .map(|i| {
+            let seed = format!("{}-{}", NONCE_SEED_PREFIX, i);
+            let account = Pubkey::create_with_seed(&base, &seed, &system_program::id())?;
+            let existing = client
+                .get_account_with_commitment(&account, client.commitment())?
+                .value;
+            if existing.is_none() {
+                info!("{} Creating nonce account {}...", id, account);
+                let instructions = system_instruction::create_nonce_account_with_seed(
+                    &base, &account, &base, &seed, &base, lamports,
+                );
+                let blockhash = client.get_latest_blockhash()?;
+                let tx = Transaction::new_signed_with_payer(
+                    &instructions,
+                    Some(&base),
+                    &[operator],
+                    blockhash,
+                );
+                client.send_and_confirm_transaction(&tx)?;
+            }
+            info!("{} Nonce account {}", id, account);
+            Ok(account)
+        })
+        .collect()
+}
+
+/// Returns blockhash stored in the nonce account.
+pub fn blockhash(client: &RpcClient, account: &Pubkey) -> Result<Hash> {
+    let account = nonce_utils::get_account_with_commitment(client, account, client.commitment())?;
+    Ok(nonce_utils::data_from_account(&account)?.blockhash)
+}
+
+/// Prepends instruction to advance the nonce account to `instructions`.
+/// The advance instruction must go first in a durable nonce transaction.
+pub fn with_advance(
+    instructions: &[Instruction],
+    account: &Pubkey,
+    authority: &Pubkey,
+) -> Vec<Instruction> {
+    let mut result = Vec::with_capacity(instructions.len() + 1);
+    result.push(system_instruction::advance_nonce_account(
+        account, authority,
+    ));
+    result.extend_from_slice(instructions);
+    result
+}
+
+#[test]
+fn test_signed() {
+    let id = crate::id::from_client(Some("test-signed"));
+    let account = Pubkey::new_unique();
+    let tx = Transaction::default();
+
+    assert!(signed(&id, "sol A 1").is_none());
+    remember(&id, "sol A 1", account, &tx);
+    assert_eq!(signed(&id, "sol A 1"), Some((account, tx.clone())));
+    let retry = crate::id::from_client(Some("test-signed"));
+    assert_eq!(signed(&retry, "sol A 1"), Some((account, tx.clone())));
+    assert!(signed(&id, "sol A 2").is_none());
+    forget(&id);
+    assert!(signed(&id, "sol A 1").is_none());
+
+    let anonymous = crate::id::generate();
+    remember(&anonymous, "sol A 1", account, &tx);
+    assert!(signed(&anonymous, "sol A 1").is_none());
+}
+
+#[test]
+fn test_with_advance() {
+    let account = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let memo = spl_memo::build_memo(b"test", &[&authority]);
+
+    let instructions = with_advance(&[memo.clone()], &account, &authority);
+    assert_eq!(instructions.len(), 2);
+    assert_eq!(instructions[0].program_id, system_program::id());
+    assert_eq!(instructions[0].accounts[0].pubkey, account);
+    assert_eq!(instructions[1], memo);
+}
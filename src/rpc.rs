@@ -0,0 +1,261 @@
+//! Faucet JSON-RPC module.
+//!
+//! Serves JSON-RPC 2.0 calls, single or batched, with the same core as the HTTP API.
+
+use actix_web::http::StatusCode;
+use actix_web::HttpRequest;
+use jsonrpc_core::{
+    Call, Error, ErrorCode, MethodCall, Output, Params, Request, Response, Version,
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tracing::{error, info};
+
+use crate::events::{self, Event};
+use crate::{api, assets, erc20_tokens, id::ReqId, neon_token, server};
+
+/// Error code of calls exceeding a cap ("Limit exceeded" of EIP-1474).
+const LIMIT_EXCEEDED: i64 = -32005;
+
+/// Maximum number of calls in a batch.
+const MAX_BATCH_LEN: usize = 20;
+
+/// Handles a JSON-RPC request, returns the response or `None` if only notifications came.
+pub async fn handle(
+    id: &ReqId,
+    req: &HttpRequest,
+    limiters: &assets::Limiters,
+    input: &[u8],
+) -> Option<Response> {
+    let request = match parse_request(id, input) {
+        Ok(request) => request,
+        Err(response) => return Some(response),
+    };
+
+    match request {
+        Request::Single(call) => process(id, req, limiters, call).await.map(Response::Single),
+        Request::Batch(calls) => {
+            info!("{} JSON-RPC batch of {} calls", id, calls.len());
+            let mut outputs = Vec::with_capacity(calls.len());
+            for call in calls {
+                outputs.extend(process(id, req, limiters, call).await);
+            }
+            if outputs.is_empty() {
+                None
+            } else {
+                Some(Response::Batch(outputs))
+            }
+        }
+    }
+}
+
+/// Parses the request, returns the error response if it's malformed
+/// or an empty or too large batch.
+fn parse_request(id: &ReqId, input: &[u8]) -> Result<Request, Response> {
+    let request = serde_json::from_slice::<Request>(input).map_err(|err| {
+        let input = String::from_utf8_lossy(input);
+        error!("{} JSON-RPC parse error: {} in '{}'", id, err, input);
+        Response::from(Error::parse_error(), Some(Version::V2))
+    })?;
+    match &request {
+        Request::Batch(calls) if calls.is_empty() => {
+            error!("{} JSON-RPC empty batch", id);
+            Err(Response::from(Error::invalid_request(), Some(Version::V2)))
+        }
+        Request::Batch(calls) if calls.len() > MAX_BATCH_LEN => {
+            let message = format!(
+                "Batch of {} calls exceeds the limit {}",
+                calls.len(),
+                MAX_BATCH_LEN
+            );
+            error!("{} JSON-RPC {}", id, message);
+            let error = Error {
+                code: ErrorCode::InvalidRequest,
+                message,
+                data: None,
+            };
+            Err(Response::from(error, Some(Version::V2)))
+        }
+        _ => Ok(request),
+    }
+}
+
+/// Processes a single call, returns its output unless the call is a notification.
+async fn process(
+    id: &ReqId,
+    req: &HttpRequest,
+    limiters: &assets::Limiters,
+    call: Call,
+) -> Option<Output> {
+    match call {
+        Call::MethodCall(MethodCall {
+            jsonrpc,
+            method,
+            params,
+            id: call_id,
+        }) => {
+            let result = execute(id, req, limiters, &method, params).await;
+            Some(Output::from(result, call_id, jsonrpc))
+        }
+        Call::Notification(notification) => {
+            let method = notification.method;
+            if let Err(err) = execute(id, req, limiters, &method, notification.params).await {
+                error!("{} JSON-RPC notification '{}': {}", id, method, err.message);
+            }
+            None
+        }
+        Call::Invalid { id: call_id } => {
+            error!("{} JSON-RPC invalid call", id);
+            Some(Output::invalid_request(call_id, Some(Version::V2)))
+        }
+    }
+}
+
+/// Executes a method of the faucet.
+async fn execute(
+    id: &ReqId,
+    req: &HttpRequest,
+    limiters: &assets::Limiters,
+    method: &str,
+    params: Params,
+) -> Result<Value, Error> {
+    info!("{} JSON-RPC call '{}'", id, method);
+    match method {
+        "faucet_version" => {
+            params.expect_no_params()?;
+            Ok(crate::version::display!().into())
+        }
+        "faucet_listTokens" => {
+            params.expect_no_params()?;
+            let list = erc20_tokens::list(id).await.map_err(|e| rejection(id, e))?;
+            serde_json::to_value(list).map_err(|e| internal(id, e))
+        }
+        "faucet_requestNeon" => {
+            let airdrop = parse::<neon_token::Airdrop>(params)?;
+            let results = airdrop_assets(id, req, limiters, assets::Airdrop::Neon(airdrop)).await?;
+            match results.into_iter().find_map(|r| r.result.error) {
+                Some(err) => Err(internal(id, err)),
+                None => Ok(Value::Null),
+            }
+        }
+        "faucet_requestErc20" => {
+            let airdrop = parse::<erc20_tokens::Airdrop>(params)?;
+            let results =
+                airdrop_assets(id, req, limiters, assets::Airdrop::Erc20(airdrop)).await?;
+            let outcome = assets::outcome(&results);
+            let results = results.iter().map(|r| &r.result).collect::<Vec<_>>();
+            let results = serde_json::to_value(results).map_err(|e| internal(id, e))?;
+            match outcome {
+                erc20_tokens::Outcome::Failure => {
+                    let message = "All transfers failed".to_string();
+                    let error = message.clone();
+                    events::publish(id, Event::Failed { error });
+                    Err(Error {
+                        code: ErrorCode::InternalError,
+                        message,
+                        data: Some(results),
+                    })
+                }
+                _ => Ok(results),
+            }
+        }
+        _ => {
+            error!("{} JSON-RPC method not found '{}'", id, method);
+            Err(Error::method_not_found())
+        }
+    }
+}
+
+/// Sends the airdrop with checking the caps.
+async fn airdrop_assets(
+    id: &ReqId,
+    req: &HttpRequest,
+    limiters: &assets::Limiters,
+    airdrop: assets::Airdrop,
+) -> Result<Vec<assets::AssetResult>, Error> {
+    assets::process(id, req, limiters, vec![airdrop])
+        .await
+        .map_err(|e| rejection(id, e))
+}
+
+/// Parses and validates parameters given as an object or as an array of one object.
+fn parse<T: DeserializeOwned + api::Validate>(params: Params) -> Result<T, Error> {
+    let value: T = match params {
+        Params::Array(mut values) if values.len() == 1 => serde_json::from_value(values.remove(0))
+            .map_err(|e| Error::invalid_params(format!("Invalid params: {}.", e)))?,
+        params => params.parse()?,
+    };
+    value.validate().map_err(Error::invalid_params)?;
+    Ok(value)
+}
+
+/// Converts an error of a rejected call into the JSON-RPC error.
+fn rejection(id: &ReqId, err: eyre::Report) -> Error {
+    let code = match server::rejection_status(&err) {
+        StatusCode::BAD_REQUEST => ErrorCode::InvalidParams,
+        StatusCode::TOO_MANY_REQUESTS => ErrorCode::ServerError(LIMIT_EXCEEDED),
+        _ => ErrorCode::InternalError,
+    };
+    error!("{} JSON-RPC error {}: {}", id, code.code(), err);
+    let message = err.to_string();
+    let error = message.clone();
+    events::publish(id, Event::Failed { error });
+    Error {
+        code,
+        message,
+        data: None,
+    }
+}
+
+/// Returns the internal JSON-RPC error.
+fn internal(id: &ReqId, err: impl std::fmt::Display) -> Error {
+    error!("{} JSON-RPC internal error: {}", id, err);
+    let message = err.to_string();
+    let error = message.clone();
+    events::publish(id, Event::Failed { error });
+    Error {
+        code: ErrorCode::InternalError,
+        message,
+        data: None,
+    }
+}
+
+#[test]
+fn test_parse() {
+    let wallet = "0x4570e07200b6332989Dc04fA2a671b839D26eF0E";
+    let params: Params =
+        serde_json::from_value(serde_json::json!([{ "wallet": wallet, "amount": 1 }])).unwrap();
+    let airdrop = parse::<neon_token::Airdrop>(params).unwrap();
+    assert_eq!(airdrop.amount, 1);
+
+    let params: Params =
+        serde_json::from_value(serde_json::json!({ "wallet": wallet, "amount": 0 })).unwrap();
+    let err = parse::<neon_token::Airdrop>(params).unwrap_err();
+    assert_eq!(err.code, ErrorCode::InvalidParams);
+
+    let params: Params = serde_json::from_value(serde_json::json!(["0x00"])).unwrap();
+    assert!(parse::<neon_token::Airdrop>(params).is_err());
+}
+
+#[test]
+fn test_parse_request() {
+    let id = crate::id::generate();
+    let call = r#"{"jsonrpc": "2.0", "method": "faucet_version", "id": 1}"#;
+    assert!(matches!(
+        parse_request(&id, call.as_bytes()),
+        Ok(Request::Single(_))
+    ));
+
+    let parse_error = Response::from(Error::parse_error(), Some(Version::V2));
+    assert_eq!(parse_request(&id, b"{\xff}").unwrap_err(), parse_error);
+
+    let batch = format!("[{}]", vec![call; MAX_BATCH_LEN].join(","));
+    assert!(parse_request(&id, batch.as_bytes()).is_ok());
+    let batch = format!("[{}]", vec![call; MAX_BATCH_LEN + 1].join(","));
+    match parse_request(&id, batch.as_bytes()) {
+        Err(Response::Single(Output::Failure(failure))) => {
+            assert_eq!(failure.error.code, ErrorCode::InvalidRequest)
+        }
+        _ => panic!("invalid request expected"),
+    }
+}
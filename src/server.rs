@@ -1,21 +1,32 @@
 //! Faucet server implementation.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::future::Future;
 use std::net::{IpAddr, ToSocketAddrs as _};
 use std::str::FromStr as _;
 use std::time::Duration;
 
 use actix_cors::Cors;
 use actix_web::http::{header, StatusCode};
-use actix_web::web::{get, post, Bytes, Data};
+use actix_web::web::{get, post, Bytes, Data, Path};
 use actix_web::{App, HttpRequest, HttpResponse, HttpServer, Responder};
 use eyre::{eyre, Result};
+use futures::stream::{self, StreamExt as _};
+use tokio::sync::broadcast::{self, error::RecvError};
 use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
 use tracing::{error, info};
 
-use crate::{active_requests, config, erc20_tokens, id, neon_token, solana};
+#[cfg(feature = "grpc")]
+use crate::grpc;
+use crate::{
+    active_requests, api, assets, config, erc20_tokens, events, id, neon_token, nft_tokens, rpc,
+    sol_token, spl_tokens, web3_client,
+};
 
-type AirdropLimiter = Data<RwLock<neon_token::AirdropLimiter>>;
+/// Airdrop limiters of all assets.
+type AirdropLimiters = Data<assets::Limiters>;
 
 /// Starts the server in listening mode.
 pub async fn start(workers: usize) -> Result<()> {
@@ -23,7 +34,7 @@ pub async fn start(workers: usize) -> Result<()> {
     let rpc_port = config::rpc_port();
     info!("{} Bind {}:{}", id::default(), rpc_bind, rpc_port);
 
-    let blacklist = config::blacklisted_ips()
+    let blacklist: HashSet<IpAddr> = config::blacklisted_ips()
         .into_iter()
         .map(|ip| IpAddr::from_str(&ip).map_err(|err| eyre!("Invalid blacklisted ip: {}", err)))
         .collect::<Result<_>>()?;
@@ -42,29 +53,135 @@ pub async fn start(workers: usize) -> Result<()> {
             .ip();
         trusted_proxies.insert(ip);
     }
-    let per_request_cap = solana::convert_whole_to_fractions(config::solana_max_amount())
-        .map_err(|err| eyre!("invalid max amount: {}", err))?;
-    let per_time_cap = solana::convert_whole_to_fractions(config::solana_per_time_max_amount())
-        .map_err(|err| eyre!("invalid per time max amount: {}", err))?;
-    let time_slice = Duration::from_secs(config::solana_time_slice_secs());
-
-    let airdrop_limiter = AirdropLimiter::new(RwLock::new(neon_token::AirdropLimiter::new(
-        trusted_proxies,
-        blacklist,
-        per_request_cap,
-        per_time_cap,
-    )));
-    let airdrop_limiter_1 = airdrop_limiter.clone();
-
-    let airdrop_limiter_reset = tokio::spawn(async move {
-        let mut clear_interval = tokio::time::interval(time_slice);
-        loop {
-            clear_interval.tick().await;
-            info!("Clearing airdrop limiter cache");
-            airdrop_limiter.write().await.clear_cache();
+    let mut tokens_refresh = None;
+    if config::web3_enabled() {
+        web3_client::init()?;
+        if let Err(err) = erc20_tokens::load(&id::default()).await {
+            error!("{} Failed loading ERC20 tokens: {}", id::default(), err);
         }
+        let refresh_interval = Duration::from_secs(config::web3_tokens_refresh_secs());
+        tokens_refresh = Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(refresh_interval);
+            // The first tick completes immediately, right after the load
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let id = id::generate();
+                info!("{} Refreshing ERC20 tokens", id);
+                if let Err(err) = erc20_tokens::refresh(&id).await {
+                    error!("{} Failed refreshing ERC20 tokens: {}", id, err);
+                }
+            }
+        }));
+    }
+
+    let mut limiters = HashMap::new();
+    for network in config::solana_networks() {
+        let per_request_cap =
+            neon_token::convert_whole_to_fractions(&network, config::solana_max_amount(&network))
+                .map_err(|err| eyre!("invalid max amount of '{}': {}", network, err))?;
+        let per_time_cap = neon_token::convert_whole_to_fractions(
+            &network,
+            config::solana_per_time_max_amount(&network),
+        )
+        .map_err(|err| eyre!("invalid per time max amount of '{}': {}", network, err))?;
+        let limiter = neon_token::AirdropLimiter::new(
+            trusted_proxies.clone(),
+            blacklist.clone(),
+            per_request_cap,
+            per_time_cap,
+        );
+        limiters.insert(network, RwLock::new(limiter));
+    }
+    let airdrop_limiters = AirdropLimiters::new(assets::Limiters {
+        neon: limiters,
+        spl: assets::TokenLimiters::new(neon_token::AirdropLimiter::new(
+            trusted_proxies.clone(),
+            blacklist.clone(),
+            0,
+            0,
+        )),
+        sol: RwLock::new(neon_token::AirdropLimiter::new(
+            trusted_proxies.clone(),
+            blacklist.clone(),
+            sol_token::sol_to_lamports(config::solana_sol_max_amount())?,
+            sol_token::sol_to_lamports(config::solana_sol_per_time_max_amount())?,
+        )),
+        nft: assets::TokenLimiters::new(neon_token::AirdropLimiter::new(
+            trusted_proxies.clone(),
+            blacklist.clone(),
+            0,
+            0,
+        )),
     });
 
+    let airdrop_limiter_resets = config::solana_networks()
+        .into_iter()
+        .map(|network| {
+            let time_slice = Duration::from_secs(config::solana_time_slice_secs(&network));
+            let airdrop_limiters = airdrop_limiters.clone();
+            tokio::spawn(async move {
+                let mut clear_interval = tokio::time::interval(time_slice);
+                loop {
+                    clear_interval.tick().await;
+                    info!("Clearing airdrop limiter cache of network '{}'", network);
+                    airdrop_limiters.neon[&network].write().await.clear_cache();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut spl_limiter_reset = None;
+    if config::solana_enabled() && !config::solana_spl_tokens().is_empty() {
+        let time_slice =
+            Duration::from_secs(config::solana_time_slice_secs(config::DEFAULT_NETWORK));
+        let airdrop_limiters = airdrop_limiters.clone();
+        spl_limiter_reset = Some(tokio::spawn(async move {
+            let mut clear_interval = tokio::time::interval(time_slice);
+            loop {
+                clear_interval.tick().await;
+                info!("Clearing airdrop limiter cache of SPL tokens");
+                airdrop_limiters.spl.clear_caches().await;
+            }
+        }));
+    }
+
+    let mut nft_limiter_reset = None;
+    if config::web3_enabled() && !config::web3_nfts().is_empty() {
+        let time_slice = Duration::from_secs(config::web3_time_slice_secs());
+        let airdrop_limiters = airdrop_limiters.clone();
+        nft_limiter_reset = Some(tokio::spawn(async move {
+            let mut clear_interval = tokio::time::interval(time_slice);
+            loop {
+                clear_interval.tick().await;
+                info!("Clearing airdrop limiter cache of NFT");
+                airdrop_limiters.nft.clear_caches().await;
+            }
+        }));
+    }
+
+    let mut sol_limiter_reset = None;
+    if config::solana_enabled() && config::solana_sol_max_amount() > 0 {
+        let time_slice =
+            Duration::from_secs(config::solana_time_slice_secs(config::DEFAULT_NETWORK));
+        let airdrop_limiters = airdrop_limiters.clone();
+        sol_limiter_reset = Some(tokio::spawn(async move {
+            let mut clear_interval = tokio::time::interval(time_slice);
+            loop {
+                clear_interval.tick().await;
+                info!("Clearing airdrop limiter cache of SOL");
+                airdrop_limiters.sol.write().await.clear_cache();
+            }
+        }));
+    }
+
+    let mut grpc = None;
+    let grpc_port = config::rpc_grpc_port();
+    if grpc_port > 0 {
+        let admin_token = config::rpc_admin_token();
+        grpc = start_grpc(&rpc_bind, grpc_port, admin_token, &airdrop_limiters)?;
+    }
+
     HttpServer::new(move || {
         let mut cors = Cors::default();
         let allowed_origins = config::allowed_origins();
@@ -72,38 +189,358 @@ pub async fn start(workers: usize) -> Result<()> {
             cors = cors
                 .allowed_methods(vec!["GET", "POST"])
                 .allowed_header(header::CONTENT_TYPE)
+                .allowed_header(id::HEADER)
+                .expose_headers(vec![id::HEADER])
                 .max_age(3600);
             for origin in &allowed_origins {
                 cors = cors.allowed_origin(origin);
             }
         }
 
-        App::new()
-            .wrap(cors)
-            .app_data(airdrop_limiter_1.clone())
-            .route("/request_ping", get().to(handle_request_ping))
-            .route("/request_version", get().to(handle_request_version))
-            .route(
-                "/request_neon_in_galans",
-                post().to(handle_request_neon_in_galans),
-            )
-            .route("/request_neon", post().to(handle_request_neon))
-            .route("/request_erc20_list", get().to(handle_request_erc20_list))
-            .route("/request_erc20", post().to(handle_request_erc20))
+        let mut app = App::new().wrap(cors).app_data(airdrop_limiters.clone());
+        for endpoint in endpoints() {
+            app = app.route(endpoint.path, (endpoint.route)());
+        }
+        app
     })
     .bind((rpc_bind, rpc_port))?
     .workers(workers)
     .run()
     .await?;
 
-    airdrop_limiter_reset.abort();
-    if let Err(err) = airdrop_limiter_reset.await {
-        error!("Error in airdrop limiter reset thread: {:?}", err);
+    for airdrop_limiter_reset in airdrop_limiter_resets {
+        airdrop_limiter_reset.abort();
+        if let Err(err) = airdrop_limiter_reset.await {
+            error!("Error in airdrop limiter reset thread: {:?}", err);
+        }
+    }
+    if let Some(spl_limiter_reset) = spl_limiter_reset {
+        spl_limiter_reset.abort();
+        if let Err(err) = spl_limiter_reset.await {
+            error!("Error in SPL airdrop limiter reset thread: {:?}", err);
+        }
+    }
+    if let Some(nft_limiter_reset) = nft_limiter_reset {
+        nft_limiter_reset.abort();
+        if let Err(err) = nft_limiter_reset.await {
+            error!("Error in NFT airdrop limiter reset thread: {:?}", err);
+        }
+    }
+    if let Some(sol_limiter_reset) = sol_limiter_reset {
+        sol_limiter_reset.abort();
+        if let Err(err) = sol_limiter_reset.await {
+            error!("Error in SOL airdrop limiter reset thread: {:?}", err);
+        }
+    }
+    if let Some(tokens_refresh) = tokens_refresh {
+        tokens_refresh.abort();
+        if let Err(err) = tokens_refresh.await {
+            error!("Error in ERC20 tokens refresh thread: {:?}", err);
+        }
+    }
+    if let Some(grpc) = grpc {
+        grpc.abort();
+        if let Err(err) = grpc.await {
+            error!("Error in gRPC server thread: {:?}", err);
+        }
     }
 
     Ok(())
 }
 
+/// Starts the gRPC server sharing the airdrop limiters.
+#[cfg(feature = "grpc")]
+fn start_grpc(
+    bind: &str,
+    port: u16,
+    admin_token: String,
+    limiters: &AirdropLimiters,
+) -> Result<Option<JoinHandle<()>>> {
+    let addr = (bind, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| eyre!("Invalid gRPC address {}:{}", bind, port))?;
+    let limiters = limiters.clone().into_inner();
+    Ok(Some(tokio::spawn(async move {
+        if let Err(err) = grpc::serve(addr, admin_token, limiters).await {
+            error!("Error in gRPC server: {:?}", err);
+        }
+    })))
+}
+
+/// Reports the gRPC server is missing in the build.
+#[cfg(not(feature = "grpc"))]
+fn start_grpc(
+    _bind: &str,
+    port: u16,
+    _admin_token: String,
+    _limiters: &AirdropLimiters,
+) -> Result<Option<JoinHandle<()>>> {
+    tracing::warn!(
+        "{} gRPC port {} is ignored: built without feature 'grpc'",
+        id::default(),
+        port
+    );
+    Ok(None)
+}
+
+/// Describes the endpoints served by the server.
+pub fn endpoints() -> Vec<api::Endpoint> {
+    use api::{json, Body, Endpoint, Method};
+    use api::{
+        BAD_REQUEST, CAP_EXCEEDED, INTERNAL_ERROR, NOT_FOUND, PARTIAL_SUCCESS, UNAUTHORIZED,
+    };
+
+    vec![
+        Endpoint {
+            method: Method::Get,
+            path: "/request_ping",
+            summary: "Requests ping to check availability of the service",
+            request: Some(Body::Text),
+            response: Some(Body::Text),
+            errors: &[],
+            route: || get().to(handle_request_ping),
+        },
+        Endpoint {
+            method: Method::Get,
+            path: "/request_version",
+            summary: "Requests version of the service",
+            request: None,
+            response: Some(Body::Text),
+            errors: &[],
+            route: || get().to(handle_request_version),
+        },
+        Endpoint {
+            method: Method::Post,
+            path: "/request_neon_in_galans",
+            summary: "Requests NEON tokens, amount in galans (fractions)",
+            request: Some(json::<neon_token::Airdrop>()),
+            response: None,
+            errors: &[BAD_REQUEST, CAP_EXCEEDED, INTERNAL_ERROR],
+            route: || post().to(handle_request_neon_in_galans),
+        },
+        Endpoint {
+            method: Method::Post,
+            path: "/request_neon",
+            summary: "Requests NEON tokens",
+            request: Some(json::<neon_token::Airdrop>()),
+            response: None,
+            errors: &[BAD_REQUEST, CAP_EXCEEDED, INTERNAL_ERROR],
+            route: || post().to(handle_request_neon),
+        },
+        Endpoint {
+            method: Method::Post,
+            path: "/request",
+            summary: "Requests several assets (NEON and ERC20 tokens) to a wallet at once",
+            request: Some(json::<assets::Request>()),
+            response: Some(json::<Vec<assets::AssetResult>>()),
+            errors: &[PARTIAL_SUCCESS, BAD_REQUEST, CAP_EXCEEDED, INTERNAL_ERROR],
+            route: || post().to(handle_request),
+        },
+        Endpoint {
+            method: Method::Get,
+            path: "/request_erc20_list",
+            summary:
+                "Requests list of available ERC20 tokens with their metadata, caps and balances",
+            request: None,
+            response: Some(json::<Vec<erc20_tokens::TokenInfo>>()),
+            errors: &[INTERNAL_ERROR],
+            route: || get().to(handle_request_erc20_list),
+        },
+        Endpoint {
+            method: Method::Post,
+            path: "/request_erc20",
+            summary: "Requests ERC20 tokens",
+            request: Some(json::<erc20_tokens::Airdrop>()),
+            response: Some(json::<Vec<erc20_tokens::TokenResult>>()),
+            errors: &[PARTIAL_SUCCESS, BAD_REQUEST, CAP_EXCEEDED, INTERNAL_ERROR],
+            route: || post().to(handle_request_erc20),
+        },
+        Endpoint {
+            method: Method::Get,
+            path: "/request_nft_list",
+            summary: "Requests list of available NFT collections with their caps and inventory",
+            request: None,
+            response: Some(json::<Vec<nft_tokens::CollectionInfo>>()),
+            errors: &[INTERNAL_ERROR],
+            route: || get().to(handle_request_nft_list),
+        },
+        Endpoint {
+            method: Method::Post,
+            path: "/request_nft",
+            summary: "Requests an NFT (ERC721) or ERC1155 tokens",
+            request: Some(json::<nft_tokens::Airdrop>()),
+            response: None,
+            errors: &[BAD_REQUEST, CAP_EXCEEDED, INTERNAL_ERROR],
+            route: || post().to(handle_request_nft),
+        },
+        Endpoint {
+            method: Method::Post,
+            path: "/request_spl",
+            summary: "Requests SPL tokens to a Solana wallet",
+            request: Some(json::<spl_tokens::Airdrop>()),
+            response: Some(Body::Text),
+            errors: &[BAD_REQUEST, CAP_EXCEEDED, INTERNAL_ERROR],
+            route: || post().to(handle_request_spl),
+        },
+        Endpoint {
+            method: Method::Post,
+            path: "/request_sol",
+            summary: "Requests SOL to a Solana wallet (if **solana**.sol_max_amount is set)",
+            request: Some(json::<sol_token::Airdrop>()),
+            response: Some(Body::Text),
+            errors: &[BAD_REQUEST, NOT_FOUND, CAP_EXCEEDED, INTERNAL_ERROR],
+            route: || post().to(handle_request_sol),
+        },
+        Endpoint {
+            method: Method::Post,
+            path: "/{network}/request_neon_in_galans",
+            summary: "Requests NEON tokens of the network, amount in galans",
+            request: Some(json::<neon_token::Airdrop>()),
+            response: None,
+            errors: &[BAD_REQUEST, CAP_EXCEEDED, INTERNAL_ERROR],
+            route: || post().to(handle_network_request_neon_in_galans),
+        },
+        Endpoint {
+            method: Method::Post,
+            path: "/{network}/request_neon",
+            summary: "Requests NEON tokens of the network",
+            request: Some(json::<neon_token::Airdrop>()),
+            response: None,
+            errors: &[BAD_REQUEST, CAP_EXCEEDED, INTERNAL_ERROR],
+            route: || post().to(handle_network_request_neon),
+        },
+        Endpoint {
+            method: Method::Post,
+            path: "/rpc",
+            summary: "Serves JSON-RPC 2.0 calls and batches of the `faucet_*` methods",
+            request: Some(json::<serde_json::Value>()),
+            response: Some(json::<serde_json::Value>()),
+            errors: &[INTERNAL_ERROR],
+            route: || post().to(handle_rpc),
+        },
+        Endpoint {
+            method: Method::Get,
+            path: "/events",
+            summary: "Streams events of all requests (SSE), requires the bearer token",
+            request: None,
+            response: Some(Body::Text),
+            errors: &[UNAUTHORIZED, NOT_FOUND],
+            route: || get().to(handle_events_feed),
+        },
+        Endpoint {
+            method: Method::Get,
+            path: "/events/{request_id}",
+            summary: "Streams lifecycle events of the request by its returned `X-Request-Id` (SSE)",
+            request: None,
+            response: Some(Body::Text),
+            errors: &[NOT_FOUND],
+            route: || get().to(handle_events),
+        },
+        Endpoint {
+            method: Method::Get,
+            path: "/v1/version",
+            summary: "Returns version of the service",
+            request: None,
+            response: Some(Body::Text),
+            errors: &[],
+            route: || get().to(handle_request_version),
+        },
+        Endpoint {
+            method: Method::Get,
+            path: "/v1/openapi.json",
+            summary: "Returns the OpenAPI document of the versioned API",
+            request: None,
+            response: Some(json::<serde_json::Value>()),
+            errors: &[INTERNAL_ERROR],
+            route: || get().to(handle_v1_openapi),
+        },
+        Endpoint {
+            method: Method::Post,
+            path: "/v1/neon",
+            summary: "Sends NEON tokens to a wallet",
+            request: Some(json::<neon_token::Airdrop>()),
+            response: None,
+            errors: &[BAD_REQUEST, CAP_EXCEEDED, INTERNAL_ERROR],
+            route: || post().to(handle_v1_neon),
+        },
+        Endpoint {
+            method: Method::Post,
+            path: "/v1/assets",
+            summary: "Sends several assets (NEON and ERC20 tokens) to a wallet at once",
+            request: Some(json::<assets::Request>()),
+            response: Some(json::<Vec<assets::AssetResult>>()),
+            errors: &[PARTIAL_SUCCESS, BAD_REQUEST, CAP_EXCEEDED, INTERNAL_ERROR],
+            route: || post().to(handle_v1_assets),
+        },
+        Endpoint {
+            method: Method::Get,
+            path: "/v1/erc20",
+            summary: "Lists available ERC20 tokens with their metadata, caps and balances",
+            request: None,
+            response: Some(json::<Vec<erc20_tokens::TokenInfo>>()),
+            errors: &[INTERNAL_ERROR],
+            route: || get().to(handle_request_erc20_list),
+        },
+        Endpoint {
+            method: Method::Post,
+            path: "/v1/erc20",
+            summary: "Sends ERC20 tokens to a wallet",
+            request: Some(json::<erc20_tokens::Airdrop>()),
+            response: Some(json::<Vec<erc20_tokens::TokenResult>>()),
+            errors: &[PARTIAL_SUCCESS, BAD_REQUEST, CAP_EXCEEDED, INTERNAL_ERROR],
+            route: || post().to(handle_v1_erc20),
+        },
+        Endpoint {
+            method: Method::Get,
+            path: "/v1/nft",
+            summary: "Lists available NFT collections with their caps and inventory",
+            request: None,
+            response: Some(json::<Vec<nft_tokens::CollectionInfo>>()),
+            errors: &[INTERNAL_ERROR],
+            route: || get().to(handle_request_nft_list),
+        },
+        Endpoint {
+            method: Method::Post,
+            path: "/v1/nft",
+            summary: "Sends an NFT (ERC721) or ERC1155 tokens to a wallet",
+            request: Some(json::<nft_tokens::Airdrop>()),
+            response: None,
+            errors: &[BAD_REQUEST, CAP_EXCEEDED, INTERNAL_ERROR],
+            route: || post().to(handle_v1_nft),
+        },
+        Endpoint {
+            method: Method::Post,
+            path: "/v1/spl",
+            summary: "Sends SPL tokens to a Solana wallet, returns the signature",
+            request: Some(json::<spl_tokens::Airdrop>()),
+            response: Some(Body::Text),
+            errors: &[BAD_REQUEST, CAP_EXCEEDED, INTERNAL_ERROR],
+            route: || post().to(handle_v1_spl),
+        },
+        Endpoint {
+            method: Method::Post,
+            path: "/v1/sol",
+            summary: "Sends SOL to a Solana wallet, returns the signature",
+            request: Some(json::<sol_token::Airdrop>()),
+            response: Some(Body::Text),
+            errors: &[BAD_REQUEST, NOT_FOUND, CAP_EXCEEDED, INTERNAL_ERROR],
+            route: || post().to(handle_v1_sol),
+        },
+    ]
+}
+
+/// Starts handling of a request of the versioned API with the id.
+fn begin(id: id::ReqId, description: &str) -> (id::ReqId, active_requests::Guard) {
+    let counter = active_requests::increment();
+
+    println!();
+    info!("{} Handling request for {}...", id, description);
+    info!("{} Active requests: {}", id, counter);
+
+    (id, counter)
+}
+
 /// Handles a ping request.
 async fn handle_request_ping(body: Bytes) -> impl Responder {
     let id = id::generate();
@@ -142,15 +579,59 @@ async fn handle_request_version() -> impl Responder {
 
 /// Handles a request for NEON airdrop in galans (1 galan = 10E-9 NEON).
 async fn handle_request_neon_in_galans(
-    limiter: AirdropLimiter,
+    limiters: AirdropLimiters,
     req: HttpRequest,
-    body: Bytes
+    body: Bytes,
 ) -> impl Responder {
-    let id = id::generate();
+    process_request_neon(limiters, req, body, None, true).await
+}
+
+/// Handles a request for NEON airdrop in galans in the network from path.
+async fn handle_network_request_neon_in_galans(
+    limiters: AirdropLimiters,
+    network: Path<String>,
+    req: HttpRequest,
+    body: Bytes,
+) -> impl Responder {
+    process_request_neon(limiters, req, body, Some(network.into_inner()), true).await
+}
+
+/// Handles a request for NEON airdrop.
+async fn handle_request_neon(
+    limiters: AirdropLimiters,
+    req: HttpRequest,
+    body: Bytes,
+) -> impl Responder {
+    process_request_neon(limiters, req, body, None, false).await
+}
+
+/// Handles a request for NEON airdrop in the network from path.
+async fn handle_network_request_neon(
+    limiters: AirdropLimiters,
+    network: Path<String>,
+    req: HttpRequest,
+    body: Bytes,
+) -> impl Responder {
+    process_request_neon(limiters, req, body, Some(network.into_inner()), false).await
+}
+
+/// Processes a request for NEON airdrop in the network from path or body.
+async fn process_request_neon(
+    limiters: AirdropLimiters,
+    req: HttpRequest,
+    body: Bytes,
+    network: Option<String>,
+    in_fractions: bool,
+) -> HttpResponse<String> {
+    let id = id::from_request(&req);
     let counter = active_requests::increment();
 
     println!();
-    info!("{} Handling request for NEON (in galans) Airdrop...", id);
+    if in_fractions {
+        info!("{} Handling request for NEON (in galans) Airdrop...", id);
+    } else {
+        info!("{} Handling request for NEON Airdrop...", id);
+    }
     info!("{} Active requests: {}", id, counter);
 
     let input = String::from_utf8(body.to_vec());
@@ -167,43 +648,55 @@ async fn handle_request_neon_in_galans(
             return HttpResponse::with_body(StatusCode::BAD_REQUEST, err.to_string());
         }
     };
-    airdrop.in_fractions = true;
+    airdrop.in_fractions = in_fractions;
 
-    match limiter.write().await.check_cache(&req, &airdrop) {
-        Ok(_) => (),
-        Err(err @ neon_token::AirdropLimiterError::BadRequest) => {
-            error!("{} BadRequest: {} in '{:?}'", id, err, airdrop);
-            return HttpResponse::with_body(StatusCode::BAD_REQUEST, err.to_string());
-        },
-        Err(neon_token::AirdropLimiterError::CapExceeded(err)) => {
-            error!("{} TooManyRequests: {} in '{:?}'", id, err, airdrop);
-            return HttpResponse::with_body(StatusCode::TOO_MANY_REQUESTS, err.to_string());
-        },
-        Err(err @ neon_token::AirdropLimiterError::BadConversion) => {
-            error!("{} InternalServerError: {} in '{:?}'", id, err, airdrop);
-            return HttpResponse::with_body(StatusCode::INTERNAL_SERVER_ERROR, err.to_string());
-        },
+    if let Some(network) = network {
+        if !airdrop.network.is_empty() && airdrop.network != network {
+            let err = format!(
+                "Conflicting networks '{}' and '{}'",
+                network, airdrop.network
+            );
+            error!("{} BadRequest: {}", id, err);
+            return HttpResponse::with_body(StatusCode::BAD_REQUEST, err);
+        }
+        airdrop.network = network;
+    }
+
+    tracked(&id, serve_neon(&id, &req, &limiters, airdrop)).await
+}
+
+/// Sends NEON of the airdrop.
+async fn serve_neon(
+    id: &id::ReqId,
+    req: &HttpRequest,
+    limiters: &assets::Limiters,
+    airdrop: neon_token::Airdrop,
+) -> HttpResponse<String> {
+    let airdrops = vec![assets::Airdrop::Neon(airdrop)];
+    let results = match assets::process(id, req, limiters, airdrops).await {
+        Ok(results) => results,
+        Err(err) => return reject(id, err),
     };
 
-    if let Err(err) = neon_token::airdrop(&id, airdrop).await {
+    if let Some(err) = results.into_iter().find_map(|r| r.result.error) {
         error!("{} InternalServerError: {}", id, err);
-        return HttpResponse::with_body(StatusCode::INTERNAL_SERVER_ERROR, err.to_string());
+        return HttpResponse::with_body(StatusCode::INTERNAL_SERVER_ERROR, err);
     }
 
     HttpResponse::with_body(StatusCode::OK, String::default())
 }
 
-/// Handles a request for NEON airdrop.
-async fn handle_request_neon(
-    limiter: AirdropLimiter,
+/// Handles a request for several assets to one wallet.
+async fn handle_request(
+    limiters: AirdropLimiters,
     req: HttpRequest,
-    body: Bytes
+    body: Bytes,
 ) -> impl Responder {
-    let id = id::generate();
+    let id = id::from_request(&req);
     let counter = active_requests::increment();
 
     println!();
-    info!("{} Handling request for NEON Airdrop...", id);
+    info!("{} Handling request for Airdrop of assets...", id);
     info!("{} Active requests: {}", id, counter);
 
     let input = String::from_utf8(body.to_vec());
@@ -213,33 +706,219 @@ async fn handle_request_neon(
     }
 
     let input = input.unwrap();
-    let airdrop = match serde_json::from_str::<neon_token::Airdrop>(&input) {
-        Ok(airdrop) => airdrop,
+    let request = match serde_json::from_str::<assets::Request>(&input) {
+        Ok(request) => request,
         Err(err) => {
             error!("{} BadRequest (json): {} in '{}'", id, err, input);
             return HttpResponse::with_body(StatusCode::BAD_REQUEST, err.to_string());
         }
     };
 
-    match limiter.write().await.check_cache(&req, &airdrop) {
-        Ok(_) => (),
-        Err(err @ neon_token::AirdropLimiterError::BadRequest) => {
-            error!("{} BadRequest: {} in '{:?}'", id, err, airdrop);
-            return HttpResponse::with_body(StatusCode::BAD_REQUEST, err.to_string());
-        },
-        Err(neon_token::AirdropLimiterError::CapExceeded(err)) => {
-            error!("{} TooManyRequests: {} in '{:?}'", id, err, airdrop);
-            return HttpResponse::with_body(StatusCode::TOO_MANY_REQUESTS, err.to_string());
+    tracked(&id, serve_assets(&id, &req, &limiters, request)).await
+}
+
+/// Sends assets of the request.
+async fn serve_assets(
+    id: &id::ReqId,
+    req: &HttpRequest,
+    limiters: &assets::Limiters,
+    request: assets::Request,
+) -> HttpResponse<String> {
+    let airdrops = match request.into_airdrops() {
+        Ok(airdrops) => airdrops,
+        Err(err) => return reject(id, err),
+    };
+    let results = match assets::process(id, req, limiters, airdrops).await {
+        Ok(results) => results,
+        Err(err) => return reject(id, err),
+    };
+
+    let status = outcome_status(id, assets::outcome(&results));
+    match serde_json::to_string(&results) {
+        Ok(body) => HttpResponse::with_body(status, body),
+        Err(err) => {
+            error!("{} InternalServerError: {}", id, err);
+            HttpResponse::with_body(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+        }
+    }
+}
+
+/// Serves the request with publishing its lifecycle events.
+async fn tracked(
+    id: &id::ReqId,
+    serve: impl Future<Output = HttpResponse<String>>,
+) -> HttpResponse<String> {
+    let _events = events::track(id);
+    let mut response = serve.await;
+    if !response.status().is_success() {
+        let error = response.body().clone();
+        events::publish(id, events::Event::Failed { error });
+    }
+    echo_id(id, &mut response);
+    response
+}
+
+/// Returns the id of the request to the client, which may watch its events.
+fn echo_id<B>(id: &id::ReqId, response: &mut HttpResponse<B>) {
+    let name = header::HeaderName::from_str(id::HEADER);
+    let value = header::HeaderValue::from_str(id.as_str());
+    if let (Ok(name), Ok(value)) = (name, value) {
+        response.headers_mut().insert(name, value);
+    }
+}
+
+/// Checks the `Authorization` header value against the bearer token in constant time.
+pub fn authorized(authorization: Option<&str>, token: &str) -> bool {
+    let bearer = match authorization.and_then(|value| value.strip_prefix("Bearer ")) {
+        Some(bearer) => bearer.as_bytes(),
+        None => return false,
+    };
+    let token = token.as_bytes();
+    bearer.len() == token.len()
+        && bearer
+            .iter()
+            .zip(token)
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Responds to a request rejected before anything is sent.
+fn reject(id: &id::ReqId, err: eyre::Report) -> HttpResponse<String> {
+    let status = rejection_status(&err);
+    match status {
+        StatusCode::BAD_REQUEST => error!("{} BadRequest: {}", id, err),
+        StatusCode::TOO_MANY_REQUESTS => error!("{} TooManyRequests: {}", id, err),
+        _ => error!("{} InternalServerError: {}", id, err),
+    }
+    HttpResponse::with_body(status, err.to_string())
+}
+
+/// Returns the status of a response to a request rejected before anything is sent.
+pub fn rejection_status(err: &eyre::Report) -> StatusCode {
+    if err.downcast_ref::<assets::InvalidRequest>().is_some()
+        || err.downcast_ref::<erc20_tokens::InvalidToken>().is_some()
+        || err.downcast_ref::<erc20_tokens::InvalidAmount>().is_some()
+        || err.downcast_ref::<nft_tokens::InvalidRequest>().is_some()
+        || err.downcast_ref::<spl_tokens::InvalidRequest>().is_some()
+        || err.downcast_ref::<sol_token::InvalidRequest>().is_some()
+    {
+        return StatusCode::BAD_REQUEST;
+    }
+    if err
+        .downcast_ref::<erc20_tokens::PerTimeCapExceeded>()
+        .is_some()
+        || err
+            .downcast_ref::<nft_tokens::PerWalletCapExceeded>()
+            .is_some()
+    {
+        return StatusCode::TOO_MANY_REQUESTS;
+    }
+    match err.downcast_ref::<neon_token::AirdropLimiterError>() {
+        Some(neon_token::AirdropLimiterError::BadRequest) => StatusCode::BAD_REQUEST,
+        Some(neon_token::AirdropLimiterError::CapExceeded(_)) => StatusCode::TOO_MANY_REQUESTS,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Returns the status of a response with results of several transfers.
+fn outcome_status(id: &id::ReqId, outcome: erc20_tokens::Outcome) -> StatusCode {
+    match outcome {
+        erc20_tokens::Outcome::Success => StatusCode::OK,
+        erc20_tokens::Outcome::PartialSuccess => {
+            error!("{} MultiStatus: some transfers failed", id);
+            StatusCode::MULTI_STATUS
+        }
+        erc20_tokens::Outcome::Failure => {
+            error!("{} InternalServerError: all transfers failed", id);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// Handles a JSON-RPC request.
+async fn handle_rpc(limiters: AirdropLimiters, req: HttpRequest, body: Bytes) -> impl Responder {
+    let id = id::from_request(&req);
+    let counter = active_requests::increment();
+
+    println!();
+    info!("{} Handling JSON-RPC request...", id);
+    info!("{} Active requests: {}", id, counter);
+
+    let _events = events::track(&id);
+    let mut response = match rpc::handle(&id, &req, &limiters, &body).await {
+        Some(response) => match serde_json::to_string(&response) {
+            Ok(response) => HttpResponse::with_body(StatusCode::OK, response),
+            Err(err) => {
+                error!("{} InternalServerError: {}", id, err);
+                HttpResponse::with_body(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+            }
         },
-        Err(neon_token::AirdropLimiterError::BadConversion) => unreachable!(),
+        None => HttpResponse::with_body(StatusCode::NO_CONTENT, String::default()),
     };
+    echo_id(&id, &mut response);
+    response
+}
 
-    if let Err(err) = neon_token::airdrop(&id, airdrop).await {
-        error!("{} InternalServerError: {}", id, err);
-        return HttpResponse::with_body(StatusCode::INTERNAL_SERVER_ERROR, err.to_string());
+/// Handles a request for lifecycle events of a single request.
+async fn handle_events(request_id: Path<String>) -> impl Responder {
+    let id = id::generate();
+    let request_id = request_id.into_inner();
+    info!("{} Streaming events of request {}", id, request_id);
+
+    match events::watch(&request_id) {
+        Some((history, receiver)) => sse(history, receiver),
+        None => {
+            error!("{} NotFound: unknown request {}", id, request_id);
+            HttpResponse::NotFound().body("Unknown request")
+        }
     }
+}
 
-    HttpResponse::with_body(StatusCode::OK, String::default())
+/// Handles a request for the global feed of events of all requests.
+async fn handle_events_feed(req: HttpRequest) -> impl Responder {
+    let id = id::generate();
+
+    let token = config::rpc_events_token();
+    if token.is_empty() {
+        error!("{} NotFound: feed of events is disabled", id);
+        return HttpResponse::NotFound().body("Feed of events is disabled");
+    }
+    let authorization = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok());
+    if !authorized(authorization, &token) {
+        error!("{} Unauthorized: invalid token of the feed of events", id);
+        return HttpResponse::Unauthorized().body("Invalid token");
+    }
+
+    info!("{} Streaming feed of events", id);
+    sse(Vec::new(), Some(events::feed()))
+}
+
+/// Responds with a stream of Server-Sent Events: the given records, then received ones.
+fn sse(
+    history: Vec<events::Record>,
+    receiver: Option<broadcast::Receiver<events::Record>>,
+) -> HttpResponse {
+    let received = stream::unfold(receiver, |receiver| async move {
+        let mut receiver = receiver?;
+        loop {
+            match receiver.recv().await {
+                Ok(record) => return Some((record, Some(receiver))),
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+    let records = stream::iter(history)
+        .chain(received)
+        .map(|record| Ok::<_, Infallible>(Bytes::from(record.to_sse())));
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .streaming(records)
 }
 
 /// Handles a request for list of available ERC20 tokens.
@@ -251,24 +930,30 @@ async fn handle_request_erc20_list() -> impl Responder {
     info!("{} Handling request for list of ERC20...", id);
     info!("{} Active requests: {}", id, counter);
 
-    let mut list = String::from("[");
-    for t in config::tokens() {
-        list.push('"');
-        list.push_str(&t);
-        list.push('"');
-        list.push(',');
-    }
-    if list.ends_with(',') {
-        list.pop();
-    }
-    list.push(']');
+    let list = match erc20_tokens::list(&id).await {
+        Ok(list) => list,
+        Err(err) => {
+            error!("{} InternalServerError: {}", id, err);
+            return HttpResponse::with_body(StatusCode::INTERNAL_SERVER_ERROR, err.to_string());
+        }
+    };
 
-    list
+    match serde_json::to_string(&list) {
+        Ok(list) => HttpResponse::with_body(StatusCode::OK, list),
+        Err(err) => {
+            error!("{} InternalServerError: {}", id, err);
+            HttpResponse::with_body(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+        }
+    }
 }
 
 /// Handles a request for ERC20 tokens airdrop.
-async fn handle_request_erc20(body: Bytes) -> impl Responder {
-    let id = id::generate();
+async fn handle_request_erc20(
+    limiters: AirdropLimiters,
+    req: HttpRequest,
+    body: Bytes,
+) -> impl Responder {
+    let id = id::from_request(&req);
     let counter = active_requests::increment();
 
     println!();
@@ -288,12 +973,295 @@ async fn handle_request_erc20(body: Bytes) -> impl Responder {
         return HttpResponse::with_body(StatusCode::BAD_REQUEST, err.to_string());
     }
 
-    if let Err(err) = erc20_tokens::airdrop(&id, airdrop.unwrap()).await {
+    tracked(&id, serve_erc20(&id, &req, &limiters, airdrop.unwrap())).await
+}
+
+/// Sends ERC20 tokens of the airdrop.
+async fn serve_erc20(
+    id: &id::ReqId,
+    req: &HttpRequest,
+    limiters: &assets::Limiters,
+    airdrop: erc20_tokens::Airdrop,
+) -> HttpResponse<String> {
+    let airdrops = vec![assets::Airdrop::Erc20(airdrop)];
+    let results = match assets::process(id, req, limiters, airdrops).await {
+        Ok(results) => results,
+        Err(err) => return reject(id, err),
+    };
+
+    let status = outcome_status(id, assets::outcome(&results));
+    let results = results.iter().map(|r| &r.result).collect::<Vec<_>>();
+    match serde_json::to_string(&results) {
+        Ok(body) => HttpResponse::with_body(status, body),
+        Err(err) => {
+            error!("{} InternalServerError: {}", id, err);
+            HttpResponse::with_body(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+        }
+    }
+}
+
+/// Handles a request for list of NFT collections.
+async fn handle_request_nft_list() -> impl Responder {
+    let id = id::generate();
+    let counter = active_requests::increment();
+
+    println!();
+    info!("{} Handling request for list of NFT...", id);
+    info!("{} Active requests: {}", id, counter);
+
+    let list = match nft_tokens::list(&id).await {
+        Ok(list) => list,
+        Err(err) => {
+            error!("{} InternalServerError: {}", id, err);
+            return HttpResponse::with_body(StatusCode::INTERNAL_SERVER_ERROR, err.to_string());
+        }
+    };
+
+    match serde_json::to_string(&list) {
+        Ok(list) => HttpResponse::with_body(StatusCode::OK, list),
+        Err(err) => {
+            error!("{} InternalServerError: {}", id, err);
+            HttpResponse::with_body(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+        }
+    }
+}
+
+/// Handles a request for NFT airdrop.
+async fn handle_request_nft(
+    limiters: AirdropLimiters,
+    req: HttpRequest,
+    body: Bytes,
+) -> impl Responder {
+    let id = id::from_request(&req);
+    let counter = active_requests::increment();
+
+    println!();
+    info!("{} Handling request for NFT Airdrop...", id);
+    info!("{} Active requests: {}", id, counter);
+
+    let input = String::from_utf8(body.to_vec());
+    if let Err(err) = input {
+        error!("{} BadRequest (body): {}", id, err);
+        return HttpResponse::with_body(StatusCode::BAD_REQUEST, err.to_string());
+    }
+
+    let input = input.unwrap();
+    let airdrop = serde_json::from_str::<nft_tokens::Airdrop>(&input);
+    if let Err(err) = airdrop {
+        error!("{} BadRequest (json): {} in '{}'", id, err, input);
+        return HttpResponse::with_body(StatusCode::BAD_REQUEST, err.to_string());
+    }
+
+    tracked(&id, serve_nft(&id, &req, &limiters, airdrop.unwrap())).await
+}
+
+/// Sends the NFT of the airdrop.
+async fn serve_nft(
+    id: &id::ReqId,
+    req: &HttpRequest,
+    limiters: &assets::Limiters,
+    airdrop: nft_tokens::Airdrop,
+) -> HttpResponse<String> {
+    match serve_single(id, req, limiters, assets::Airdrop::Nft(airdrop)).await {
+        Ok(_) => HttpResponse::with_body(StatusCode::OK, String::default()),
+        Err(response) => response,
+    }
+}
+
+/// Handles a request for SPL tokens airdrop to a Solana wallet.
+async fn handle_request_spl(
+    limiters: AirdropLimiters,
+    req: HttpRequest,
+    body: Bytes,
+) -> impl Responder {
+    let id = id::from_request(&req);
+    let counter = active_requests::increment();
+
+    println!();
+    info!("{} Handling request for SPL Airdrop...", id);
+    info!("{} Active requests: {}", id, counter);
+
+    let input = String::from_utf8(body.to_vec());
+    if let Err(err) = input {
+        error!("{} BadRequest (body): {}", id, err);
+        return HttpResponse::with_body(StatusCode::BAD_REQUEST, err.to_string());
+    }
+
+    let input = input.unwrap();
+    let airdrop = match serde_json::from_str::<spl_tokens::Airdrop>(&input) {
+        Ok(airdrop) => airdrop,
+        Err(err) => {
+            error!("{} BadRequest (json): {} in '{}'", id, err, input);
+            return HttpResponse::with_body(StatusCode::BAD_REQUEST, err.to_string());
+        }
+    };
+
+    tracked(&id, serve_spl(&id, &req, &limiters, airdrop)).await
+}
+
+/// Sends SPL tokens of the airdrop.
+async fn serve_spl(
+    id: &id::ReqId,
+    req: &HttpRequest,
+    limiters: &assets::Limiters,
+    airdrop: spl_tokens::Airdrop,
+) -> HttpResponse<String> {
+    match serve_single(id, req, limiters, assets::Airdrop::Spl(airdrop)).await {
+        Ok(signature) => HttpResponse::with_body(StatusCode::OK, signature),
+        Err(response) => response,
+    }
+}
+
+/// Sends the airdrop of a single asset with checking the caps, returns the transaction.
+async fn serve_single(
+    id: &id::ReqId,
+    req: &HttpRequest,
+    limiters: &assets::Limiters,
+    airdrop: assets::Airdrop,
+) -> Result<String, HttpResponse<String>> {
+    let results = assets::process(id, req, limiters, vec![airdrop])
+        .await
+        .map_err(|err| reject(id, err))?;
+    if let Some(err) = results.iter().find_map(|r| r.result.error.as_ref()) {
         error!("{} InternalServerError: {}", id, err);
-        return HttpResponse::with_body(StatusCode::INTERNAL_SERVER_ERROR, err.to_string());
+        return Err(HttpResponse::with_body(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            err.clone(),
+        ));
     }
+    Ok(results
+        .into_iter()
+        .find_map(|r| r.result.tx)
+        .unwrap_or_default())
+}
 
-    HttpResponse::with_body(StatusCode::OK, String::default())
+/// Handles a request for SOL airdrop to a Solana wallet.
+async fn handle_request_sol(
+    limiters: AirdropLimiters,
+    req: HttpRequest,
+    body: Bytes,
+) -> impl Responder {
+    let id = id::from_request(&req);
+    let counter = active_requests::increment();
+
+    println!();
+    info!("{} Handling request for SOL Airdrop...", id);
+    info!("{} Active requests: {}", id, counter);
+
+    let input = String::from_utf8(body.to_vec());
+    if let Err(err) = input {
+        error!("{} BadRequest (body): {}", id, err);
+        return HttpResponse::with_body(StatusCode::BAD_REQUEST, err.to_string());
+    }
+
+    let input = input.unwrap();
+    let airdrop = match serde_json::from_str::<sol_token::Airdrop>(&input) {
+        Ok(airdrop) => airdrop,
+        Err(err) => {
+            error!("{} BadRequest (json): {} in '{}'", id, err, input);
+            return HttpResponse::with_body(StatusCode::BAD_REQUEST, err.to_string());
+        }
+    };
+
+    tracked(&id, serve_sol(&id, &req, &limiters, airdrop)).await
+}
+
+/// Sends SOL of the airdrop.
+async fn serve_sol(
+    id: &id::ReqId,
+    req: &HttpRequest,
+    limiters: &assets::Limiters,
+    airdrop: sol_token::Airdrop,
+) -> HttpResponse<String> {
+    if !config::solana_enabled() || config::solana_sol_max_amount() == 0 {
+        error!("{} NotFound: SOL airdrops are disabled", id);
+        return HttpResponse::with_body(
+            StatusCode::NOT_FOUND,
+            "SOL airdrops are disabled".to_string(),
+        );
+    }
+
+    match serve_single(id, req, limiters, assets::Airdrop::Sol(airdrop)).await {
+        Ok(signature) => HttpResponse::with_body(StatusCode::OK, signature),
+        Err(response) => response,
+    }
+}
+
+/// Handles a request for the OpenAPI document.
+async fn handle_v1_openapi() -> impl Responder {
+    let (id, _counter) = begin(id::generate(), "OpenAPI document");
+
+    match serde_json::to_string(&api::openapi(&endpoints())) {
+        Ok(doc) => HttpResponse::with_body(StatusCode::OK, doc),
+        Err(err) => {
+            error!("{} InternalServerError: {}", id, err);
+            HttpResponse::with_body(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+        }
+    }
+}
+
+/// Handles a validated request for NEON airdrop.
+async fn handle_v1_neon(
+    limiters: AirdropLimiters,
+    req: HttpRequest,
+    airdrop: api::Valid<neon_token::Airdrop>,
+) -> impl Responder {
+    let (id, _counter) = begin(id::from_request(&req), "NEON Airdrop");
+    tracked(&id, serve_neon(&id, &req, &limiters, airdrop.into_inner())).await
+}
+
+/// Handles a validated request for several assets to one wallet.
+async fn handle_v1_assets(
+    limiters: AirdropLimiters,
+    req: HttpRequest,
+    request: api::Valid<assets::Request>,
+) -> impl Responder {
+    let (id, _counter) = begin(id::from_request(&req), "Airdrop of assets");
+    tracked(
+        &id,
+        serve_assets(&id, &req, &limiters, request.into_inner()),
+    )
+    .await
+}
+
+/// Handles a validated request for ERC20 tokens airdrop.
+async fn handle_v1_erc20(
+    limiters: AirdropLimiters,
+    req: HttpRequest,
+    airdrop: api::Valid<erc20_tokens::Airdrop>,
+) -> impl Responder {
+    let (id, _counter) = begin(id::from_request(&req), "ERC20 Airdrop");
+    tracked(&id, serve_erc20(&id, &req, &limiters, airdrop.into_inner())).await
+}
+
+/// Handles a validated request for NFT airdrop.
+async fn handle_v1_nft(
+    limiters: AirdropLimiters,
+    req: HttpRequest,
+    airdrop: api::Valid<nft_tokens::Airdrop>,
+) -> impl Responder {
+    let (id, _counter) = begin(id::from_request(&req), "NFT Airdrop");
+    tracked(&id, serve_nft(&id, &req, &limiters, airdrop.into_inner())).await
+}
+
+/// Handles a validated request for SPL tokens airdrop.
+async fn handle_v1_spl(
+    limiters: AirdropLimiters,
+    req: HttpRequest,
+    airdrop: api::Valid<spl_tokens::Airdrop>,
+) -> impl Responder {
+    let (id, _counter) = begin(id::from_request(&req), "SPL Airdrop");
+    tracked(&id, serve_spl(&id, &req, &limiters, airdrop.into_inner())).await
+}
+
+/// Handles a validated request for SOL airdrop.
+async fn handle_v1_sol(
+    limiters: AirdropLimiters,
+    req: HttpRequest,
+    airdrop: api::Valid<sol_token::Airdrop>,
+) -> impl Responder {
+    let (id, _counter) = begin(id::from_request(&req), "SOL Airdrop");
+    tracked(&id, serve_sol(&id, &req, &limiters, airdrop.into_inner())).await
 }
 
 /// Handles a request for graceful shutdown.
@@ -0,0 +1,138 @@
+//! Faucet SOL module.
+//!
+//! Gives SOL to Solana wallets of operators and developers on local clusters.
+//! Uses the `requestAirdrop` RPC of test validators or sends SOL from the operator.
+
+use std::str::FromStr as _;
+
+use eyre::{eyre, Result};
+use tracing::{info, warn};
+
+use solana_sdk::native_token::LAMPORTS_PER_SOL;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+
+use crate::{api, config, id::ReqId, solana};
+
+/// Represents packet of information needed for a SOL airdrop operation.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct Airdrop {
+    /// Solana wallet of the recipient (base58).
+    pub wallet: String,
+    /// Amount of SOL to be received.
+    pub amount: u64,
+    /// Specifies amount in whole SOL (false, default) or in lamports (true).
+    #[serde(default)]
+    pub in_lamports: bool,
+}
+
+impl api::Validate for Airdrop {
+    fn validate(&self) -> Result<(), String> {
+        Pubkey::from_str(&self.wallet)
+            .map_err(|e| format!("Invalid wallet '{}': {}", self.wallet, e))?;
+        if self.amount == 0 {
+            return Err("Amount must be positive".into());
+        }
+        Ok(())
+    }
+}
+
+/// Represents an error of a request which can't be served.
+#[derive(thiserror::Error, Debug)]
+#[error("{0}")]
+pub struct InvalidRequest(String);
+
+/// Resolves the recipient and converts the amount into lamports.
+pub fn prepare(id: &ReqId, params: &Airdrop) -> Result<(Pubkey, u64)> {
+    info!("{} Processing SOL {:?}...", id, params);
+
+    let wallet = Pubkey::from_str(&params.wallet)
+        .map_err(|e| InvalidRequest(format!("Invalid wallet '{}': {}", params.wallet, e)))?;
+    let lamports = if params.in_lamports {
+        params.amount
+    } else {
+        sol_to_lamports(params.amount).map_err(|e| InvalidRequest(e.to_string()))?
+    };
+    Ok((wallet, lamports))
+}
+
+/// Requests or sends SOL to the wallet, returns the signature.
+pub async fn airdrop(id: &ReqId, wallet: Pubkey, lamports: u64) -> Result<Signature> {
+    let mode = config::solana_sol_mode();
+    let signature = if mode == config::SOL_MODE_TRANSFER {
+        transfer(id, wallet, lamports).await?
+    } else {
+        info!(
+            "{} Request airdrop of {} lamports -> {}",
+            id, lamports, wallet
+        );
+        match solana::request_airdrop(id, wallet, lamports).await {
+            Ok(signature) => signature,
+            // A sent airdrop may still land, so only a rejected one falls back
+            Err(e) if mode == config::SOL_MODE_AUTO && e.is::<solana::AirdropRejected>() => {
+                warn!("{} Airdrop failed, sending from the operator: {}", id, e);
+                transfer(id, wallet, lamports).await?
+            }
+            Err(e) => return Err(e),
+        }
+    };
+
+    info!("{} OK {}", id, signature);
+    Ok(signature)
+}
+
+/// Sends SOL from the operator account.
+async fn transfer(id: &ReqId, wallet: Pubkey, lamports: u64) -> Result<Signature> {
+    let operator = config::solana_operator_keypair()
+        .map_err(|e| eyre!("config::solana_operator_keypair: {:?}", e))?;
+    info!("{} Transfer {} lamports -> {}", id, lamports, wallet);
+    solana::transfer_sol(id, operator, wallet, lamports).await
+}
+
+/// Converts amount of whole SOL into lamports.
+pub fn sol_to_lamports(amount: u64) -> Result<u64> {
+    amount
+        .checked_mul(LAMPORTS_PER_SOL)
+        .ok_or_else(|| eyre!("Overflow {}*{}", amount, LAMPORTS_PER_SOL))
+}
+
+#[test]
+fn test_sol_to_lamports() {
+    assert_eq!(sol_to_lamports(0).unwrap(), 0);
+    assert_eq!(sol_to_lamports(2).unwrap(), 2_000_000_000);
+    assert!(sol_to_lamports(u64::MAX).is_err());
+}
+
+#[test]
+fn test_prepare() {
+    let wallet = Pubkey::new_unique();
+    let id = ReqId::default();
+
+    let airdrop = |amount, in_lamports| Airdrop {
+        wallet: wallet.to_string(),
+        amount,
+        in_lamports,
+    };
+    assert_eq!(
+        prepare(&id, &airdrop(3, false)).unwrap(),
+        (wallet, 3_000_000_000)
+    );
+    assert_eq!(prepare(&id, &airdrop(3, true)).unwrap(), (wallet, 3));
+    assert_eq!(
+        prepare(&id, &airdrop(u64::MAX, true)).unwrap(),
+        (wallet, u64::MAX)
+    );
+
+    let err = prepare(&id, &airdrop(u64::MAX, false)).unwrap_err();
+    assert!(err.is::<InvalidRequest>());
+    let err = prepare(
+        &id,
+        &Airdrop {
+            wallet: "wallet".into(),
+            amount: 1,
+            in_lamports: true,
+        },
+    )
+    .unwrap_err();
+    assert!(err.is::<InvalidRequest>());
+}
@@ -1,25 +1,33 @@
 //! Faucet Solana utilities module.
 
+use std::collections::HashMap;
 use std::str::FromStr as _;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use eyre::{eyre, Result, WrapErr as _};
-use tracing::debug;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+use tracing::{debug, error, info, warn};
 
 use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSendTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::Hash;
 use solana_sdk::instruction::{AccountMeta, Instruction};
 use solana_sdk::message::Message;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::signature::Signer as _;
+use solana_sdk::signature::{Signature, Signer as _};
 use solana_sdk::signer::keypair::Keypair;
-use solana_sdk::system_program;
-use solana_sdk::transaction::Transaction;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use solana_sdk::{system_instruction, system_program};
 
 use crate::config;
-use crate::{ethereum, id::ReqId};
+use crate::events::{self, Event};
+use crate::{ethereum, id::ReqId, nonce};
 
 /// Converts amount of tokens from whole value to fractions (usually 10E-9).
-pub fn convert_whole_to_fractions(amount: u64) -> Result<u64> {
-    let decimals = config::solana_token_mint_decimals();
+pub fn convert_whole_to_fractions(network: &str, amount: u64) -> Result<u64> {
+    let decimals = config::solana_token_mint_decimals(network);
     let factor = 10_u64
         .checked_pow(decimals as u32)
         .ok_or_else(|| eyre!("Overflow 10^{}", decimals))?;
@@ -28,28 +36,60 @@ pub fn convert_whole_to_fractions(amount: u64) -> Result<u64> {
         .ok_or_else(|| eyre!("Overflow {}*{}", amount, factor))
 }
 
+lazy_static::lazy_static! {
+    static ref DEPOSIT_QUEUES: std::sync::Mutex<HashMap<Pubkey, Arc<Mutex<()>>>> =
+        std::sync::Mutex::new(HashMap::new());
+}
+
+/// Waits in the queue of transactions using the `account` (FIFO).
+pub async fn lock_account(account: Pubkey) -> OwnedMutexGuard<()> {
+    let queue = DEPOSIT_QUEUES
+        .lock()
+        .unwrap()
+        .entry(account)
+        .or_default()
+        .clone();
+    queue.lock_owned().await
+}
+
+/// Represents accounts taking part in a deposit.
+struct DepositAccounts {
+    signer: Pubkey,
+    signer_token: Pubkey,
+    evm_pool: Pubkey,
+    ether_account: Pubkey,
+    evm_loader: Pubkey,
+}
+
 /// Deposits `amount` of tokens from main account to associated account.
 /// When `in_fractions` == false, amount is treated as whole token amount.
 /// When `in_fractions` == true, amount is treated as amount in galans (10E-9).
+/// When `commitment` is given, waits for it even in fire-and-forget mode.
 pub async fn deposit_token(
     id: &ReqId,
+    network: &str,
     signer: Keypair,
     ether_address: ethereum::Address,
     amount: u64,
     in_fractions: bool,
-) -> Result<()> {
-    let evm_loader_id = Pubkey::from_str(&config::solana_evm_loader()).wrap_err_with(|| {
-        eyre!(
-            "config::solana_evm_loader returns {}",
-            &config::solana_evm_loader()
-        )
-    })?;
-    let token_mint_id = Pubkey::from_str(&config::solana_token_mint_id()).wrap_err_with(|| {
-        eyre!(
-            "config::solana_token_mint_id returns {}",
-            &config::solana_token_mint_id(),
-        )
-    })?;
+    commitment: Option<CommitmentConfig>,
+) -> Result<Signature> {
+    let evm_loader_id =
+        Pubkey::from_str(&config::solana_evm_loader(network)).wrap_err_with(|| {
+            eyre!(
+                "config::solana_evm_loader({}) returns {}",
+                network,
+                &config::solana_evm_loader(network)
+            )
+        })?;
+    let token_mint_id =
+        Pubkey::from_str(&config::solana_token_mint_id(network)).wrap_err_with(|| {
+            eyre!(
+                "config::solana_token_mint_id({}) returns {}",
+                network,
+                &config::solana_token_mint_id(network),
+            )
+        })?;
 
     let signer_pubkey = signer.pubkey();
     let signer_token_pubkey =
@@ -61,70 +101,659 @@ pub async fn deposit_token(
         &token_mint_id,
     );
 
-    let ether_pubkey = ether_address_to_solana_pubkey(&ether_address, &evm_loader_id).0;
+    let seed_version = config::solana_account_seed_version(network);
+    let ether_pubkey =
+        ether_address_to_solana_pubkey(&ether_address, &evm_loader_id, seed_version).0;
+
+    let amount = if in_fractions {
+        amount
+    } else {
+        convert_whole_to_fractions(network, amount)?
+    };
+
+    let accounts = DepositAccounts {
+        signer: signer_pubkey,
+        signer_token: signer_token_pubkey,
+        evm_pool: evm_pool_pubkey,
+        ether_account: ether_pubkey,
+        evm_loader: evm_loader_id,
+    };
+
+    // Approve sets the single delegate of the token account; approve + deposit
+    // go in one atomic transaction, so the queue is held only until it's sent
+    let guard = lock_account(signer_token_pubkey).await;
+
+    let purpose = format!("deposit {} {:?} {}", network, ether_address, amount);
+    let (nonce, nonce_guard) = acquire_nonce(id, purpose).await?;
+
+    let simulate = config::solana_simulate();
+    let preflight = config::solana_preflight_commitment();
+    let confirm = commitment.unwrap_or_else(config::solana_confirm_commitment);
+    let background = commitment.is_none() && config::solana_fire_and_forget();
 
     let id = id.to_owned();
-    tokio::task::spawn_blocking(move || -> Result<()> {
+    let bg_id = id.clone();
+    let sent = tokio::task::spawn_blocking(move || -> Result<_> {
         let client =
             RpcClient::new_with_commitment(config::solana_url(), config::solana_commitment());
+        let instructions = deposit_instructions(&id, &accounts, ether_address, amount);
+        let sent = send_transaction(
+            &client,
+            &id,
+            &signer,
+            &instructions,
+            simulate,
+            preflight,
+            nonce.as_ref(),
+        );
+        drop(guard);
+        let sent = sent?;
+        if !background {
+            confirm_transaction(&client, &id, &sent, confirm)?;
+        }
+        Ok((client, sent))
+    })
+    .await?;
 
-        let amount = if in_fractions {
-            amount
-        } else {
-            convert_whole_to_fractions(amount)?
-        };
+    let (client, sent) = sent?;
+    let signature = sent.signature();
+    if background {
+        info!(
+            "{} Confirming transaction {} in background",
+            bg_id, signature
+        );
+        let tracker = events::hold(&bg_id);
+        tokio::task::spawn_blocking(move || {
+            let _guards = (nonce_guard, tracker);
+            if let Err(err) = confirm_transaction(&client, &bg_id, &sent, confirm) {
+                error!(
+                    "{} Transaction {} is not confirmed: {}",
+                    bg_id, signature, err
+                );
+                let error = err.to_string();
+                events::publish(&bg_id, Event::Failed { error });
+            }
+        });
+    }
 
+    Ok(signature)
+}
+
+/// Returns instructions of a deposit transaction.
+fn deposit_instructions(
+    id: &ReqId,
+    accounts: &DepositAccounts,
+    ether_address: ethereum::Address,
+    amount: u64,
+) -> Vec<Instruction> {
+    vec![
+        spl_memo(id, &accounts.signer),
+        spl_approve_instruction(
+            id,
+            spl_token::id(),
+            accounts.signer_token,
+            accounts.ether_account,
+            accounts.signer,
+            amount,
+        ),
+        deposit_instruction(
+            id,
+            ether_address,
+            accounts.signer_token,
+            accounts.evm_pool,
+            accounts.ether_account,
+            accounts.evm_loader,
+            spl_token::id(),
+            accounts.signer,
+        ),
+    ]
+}
+
+/// Represents a transfer of an SPL token from the associated token account of the signer.
+#[derive(Debug, Clone)]
+pub struct SplTransfer {
+    pub mint: Pubkey,
+    /// Amount in token fractions.
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+/// Transfers SPL tokens from associated token accounts of the `signer`
+/// to associated token accounts of the `wallet` in one transaction.
+/// Missing associated token accounts of the wallet are created.
+pub async fn transfer_spl(
+    id: &ReqId,
+    signer: Keypair,
+    wallet: Pubkey,
+    transfers: Vec<SplTransfer>,
+) -> Result<Signature> {
+    // Concurrent requests to the same wallet must not create its accounts twice
+    let _guard = lock_account(wallet).await;
+
+    let amounts = transfers
+        .iter()
+        .map(|t| format!("{}:{}", t.mint, t.amount))
+        .collect::<Vec<_>>();
+    let purpose = format!("spl {} {}", wallet, amounts.join(","));
+    let (nonce, _nonce_guard) = acquire_nonce(id, purpose).await?;
+
+    let simulate = config::solana_simulate();
+    let preflight = config::solana_preflight_commitment();
+    let confirm = config::solana_confirm_commitment();
+
+    let id = id.to_owned();
+    tokio::task::spawn_blocking(move || -> Result<Signature> {
+        let client =
+            RpcClient::new_with_commitment(config::solana_url(), config::solana_commitment());
+        let mut missing = Vec::with_capacity(transfers.len());
+        for t in &transfers {
+            let account =
+                spl_associated_token_account::get_associated_token_address(&wallet, &t.mint);
+            let existing = client
+                .get_account_with_commitment(&account, client.commitment())?
+                .value;
+            missing.push(existing.is_none());
+        }
+        let instructions =
+            spl_transfer_instructions(&id, &signer.pubkey(), &wallet, &transfers, &missing)?;
+        let sent = send_transaction(
+            &client,
+            &id,
+            &signer,
+            &instructions,
+            simulate,
+            preflight,
+            nonce.as_ref(),
+        )?;
+        confirm_transaction(&client, &id, &sent, confirm)?;
+        Ok(sent.signature())
+    })
+    .await?
+}
+
+/// Sends `lamports` from the `signer` account to the `wallet`, returns the signature.
+pub async fn transfer_sol(
+    id: &ReqId,
+    signer: Keypair,
+    wallet: Pubkey,
+    lamports: u64,
+) -> Result<Signature> {
+    let purpose = format!("sol {} {}", wallet, lamports);
+    let (nonce, _nonce_guard) = acquire_nonce(id, purpose).await?;
+
+    let simulate = config::solana_simulate();
+    let preflight = config::solana_preflight_commitment();
+    let confirm = config::solana_confirm_commitment();
+
+    let id = id.to_owned();
+    tokio::task::spawn_blocking(move || -> Result<Signature> {
+        let client =
+            RpcClient::new_with_commitment(config::solana_url(), config::solana_commitment());
+        debug!("{} Instruction: Transfer {} lamports", id, lamports);
         let instructions = vec![
-            spl_memo(&id, &signer_pubkey),
-            spl_approve_instruction(
-                &id,
-                spl_token::id(),
-                signer_token_pubkey,
-                ether_pubkey,
-                signer_pubkey,
-                amount,
-            ),
-            deposit_instruction(
-                &id,
-                ether_address,
-                signer_token_pubkey,
-                evm_pool_pubkey,
-                ether_pubkey,
-                evm_loader_id,
-                spl_token::id(),
-                signer_pubkey,
-            ),
+            spl_memo(&id, &signer.pubkey()),
+            system_instruction::transfer(&signer.pubkey(), &wallet, lamports),
         ];
+        let sent = send_transaction(
+            &client,
+            &id,
+            &signer,
+            &instructions,
+            simulate,
+            preflight,
+            nonce.as_ref(),
+        )?;
+        confirm_transaction(&client, &id, &sent, confirm)?;
+        Ok(sent.signature())
+    })
+    .await?
+}
+
+/// Longest wait for confirmation of a `requestAirdrop` transaction.
+const AIRDROP_CONFIRM_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Represents a rejection of `requestAirdrop`: nothing has been sent.
+#[derive(thiserror::Error, Debug)]
+#[error("requestAirdrop is rejected: {0}")]
+pub struct AirdropRejected(String);
 
+/// Requests `lamports` for the `wallet` with the `requestAirdrop` RPC of a test validator.
+/// Returns the signature of the airdrop transaction once it's confirmed.
+pub async fn request_airdrop(id: &ReqId, wallet: Pubkey, lamports: u64) -> Result<Signature> {
+    let confirm = config::solana_confirm_commitment();
+
+    let id = id.to_owned();
+    tokio::task::spawn_blocking(move || -> Result<Signature> {
+        let client =
+            RpcClient::new_with_commitment(config::solana_url(), config::solana_commitment());
+        let signature = client
+            .request_airdrop(&wallet, lamports)
+            .map_err(|e| AirdropRejected(e.to_string()))?;
+        let sent = Event::SignatureSent {
+            signature: signature.to_string(),
+        };
+        events::publish(&id, sent);
         debug!(
-            "{} Creating message with {} instructions...",
-            id,
-            instructions.len()
+            "{} Confirming airdrop {} with commitment {:?}...",
+            id, signature, confirm.commitment
         );
-        let message = Message::new(&instructions, Some(&signer_pubkey));
-        debug!("{} Creating transaction...", id);
-        let mut tx = Transaction::new_unsigned(message);
-        debug!("{} Getting latest blockhash...", id);
-        let blockhash = client.get_latest_blockhash()?;
-        debug!("{} Signing transaction...", id);
-        tx.try_sign(&[&signer], blockhash)?;
-        debug!("{} Sending and confirming transaction...", id);
-        client.send_and_confirm_transaction(&tx)?;
-        debug!("{} Transaction is confirmed", id);
-
-        Ok(())
+        let started = std::time::Instant::now();
+        loop {
+            match client.get_signature_status_with_commitment(&signature, confirm)? {
+                Some(Ok(())) => {
+                    let confirmed = Event::Confirmed {
+                        signature: signature.to_string(),
+                    };
+                    events::publish(&id, confirmed);
+                    return Ok(signature);
+                }
+                Some(Err(err)) => return Err(err.into()),
+                None if started.elapsed() > AIRDROP_CONFIRM_TIMEOUT => {
+                    return Err(eyre!("Airdrop {} is not confirmed", signature));
+                }
+                None => std::thread::sleep(CONFIRM_POLL_INTERVAL),
+            }
+        }
     })
     .await?
 }
 
+/// Returns instructions of an SPL transfer transaction.
+/// Flags of `missing` tell which associated token accounts of the wallet to create first.
+fn spl_transfer_instructions(
+    id: &ReqId,
+    signer: &Pubkey,
+    wallet: &Pubkey,
+    transfers: &[SplTransfer],
+    missing: &[bool],
+) -> Result<Vec<Instruction>> {
+    use spl_associated_token_account::{
+        create_associated_token_account, get_associated_token_address,
+    };
+
+    let mut instructions = vec![spl_memo(id, signer)];
+    for (t, missing) in transfers.iter().zip(missing) {
+        let destination = get_associated_token_address(wallet, &t.mint);
+        if *missing {
+            debug!("{} Instruction: CreateAssociatedTokenAccount", id);
+            instructions.push(create_associated_token_account(signer, wallet, &t.mint));
+        }
+        debug!("{} Instruction: TokenInstruction::TransferChecked", id);
+        instructions.push(spl_token::instruction::transfer_checked(
+            &spl_token::id(),
+            &get_associated_token_address(signer, &t.mint),
+            &t.mint,
+            &destination,
+            signer,
+            &[],
+            t.amount,
+            t.decimals,
+        )?);
+    }
+    Ok(instructions)
+}
+
+/// Represents the source of the recent blockhash of a transaction.
+#[derive(Debug, Clone, Copy)]
+enum Recency {
+    /// Latest blockhash, expires in about 2 minutes.
+    Blockhash(Hash),
+    /// Durable nonce, valid until the nonce account is advanced.
+    Nonce { account: Pubkey, hash: Hash },
+}
+
+/// Represents a sent transaction to be confirmed.
+struct SentTransaction {
+    tx: Transaction,
+    recency: Recency,
+}
+
+impl SentTransaction {
+    fn signature(&self) -> Signature {
+        self.tx.signatures[0]
+    }
+}
+
+/// Durable nonce account taken for a transaction of the `purpose`.
+struct DurableNonce {
+    account: Pubkey,
+    purpose: String,
+}
+
+/// Takes a durable nonce account if durable nonces are enabled.
+async fn acquire_nonce(
+    id: &ReqId,
+    purpose: String,
+) -> Result<(Option<DurableNonce>, Option<OwnedMutexGuard<()>>)> {
+    Ok(match nonce::acquire(id, &purpose).await? {
+        Some((account, guard)) => (Some(DurableNonce { account, purpose }), Some(guard)),
+        None => (None, None),
+    })
+}
+
+/// Builds, signs and sends a transaction of `instructions`.
+/// When `simulate` == true, the transaction is simulated before sending.
+/// When `nonce` is given, the transaction uses the durable nonce; a retry of
+/// the request re-sends the transaction signed before instead of building a new one.
+fn send_transaction(
+    client: &RpcClient,
+    id: &ReqId,
+    signer: &Keypair,
+    instructions: &[Instruction],
+    simulate: bool,
+    preflight: CommitmentConfig,
+    nonce: Option<&DurableNonce>,
+) -> Result<SentTransaction> {
+    let config = RpcSendTransactionConfig {
+        preflight_commitment: Some(preflight.commitment),
+        ..RpcSendTransactionConfig::default()
+    };
+
+    let (instructions, recency) = match nonce {
+        Some(DurableNonce { account, purpose }) => {
+            let account = *account;
+            debug!("{} Getting durable nonce of {}...", id, account);
+            let hash = nonce::blockhash(client, &account)?;
+            if let Some(sent) = resend_signed(client, id, account, purpose, hash)? {
+                return Ok(sent);
+            }
+            let instructions = nonce::with_advance(instructions, &account, &signer.pubkey());
+            (instructions, Recency::Nonce { account, hash })
+        }
+        None => {
+            debug!("{} Getting latest blockhash...", id);
+            let blockhash = client.get_latest_blockhash()?;
+            (instructions.to_vec(), Recency::Blockhash(blockhash))
+        }
+    };
+    let blockhash = match recency {
+        Recency::Blockhash(hash) | Recency::Nonce { hash, .. } => hash,
+    };
+
+    debug!(
+        "{} Creating message with {} instructions...",
+        id,
+        instructions.len()
+    );
+    let message = Message::new(&instructions, Some(&signer.pubkey()));
+    debug!("{} Creating transaction...", id);
+    let mut tx = Transaction::new_unsigned(message);
+    debug!("{} Signing transaction...", id);
+    tx.try_sign(&[signer], blockhash)?;
+    events::publish(id, Event::TxBuilt);
+    if simulate {
+        simulate_transaction(client, id, &tx)?;
+    }
+    if let (Some(nonce), Recency::Nonce { account, .. }) = (nonce, &recency) {
+        nonce::remember(id, &nonce.purpose, *account, &tx);
+    }
+    debug!("{} Sending transaction...", id);
+    let signature = client.send_transaction_with_config(&tx, config)?;
+    debug!("{} Transaction {} is sent", id, signature);
+    let signature = signature.to_string();
+    events::publish(id, Event::SignatureSent { signature });
+
+    Ok(SentTransaction { tx, recency })
+}
+
+/// Re-sends the durable nonce transaction signed for a previous attempt of the request.
+/// Returns `None` if there is no such transaction or it can't land anymore.
+fn resend_signed(
+    client: &RpcClient,
+    id: &ReqId,
+    account: Pubkey,
+    purpose: &str,
+    hash: Hash,
+) -> Result<Option<SentTransaction>> {
+    let tx = match nonce::signed(id, purpose) {
+        Some((signed_account, tx)) if signed_account == account => tx,
+        _ => return Ok(None),
+    };
+    let signature = tx.signatures[0];
+    let signed_hash = tx.message.recent_blockhash;
+    if signed_hash != hash && client.get_signature_status(&signature)?.is_none() {
+        // The nonce advanced without the transaction, so it will never land
+        info!(
+            "{} Transaction {} is dropped, signing a new one",
+            id, signature
+        );
+        nonce::forget(id);
+        return Ok(None);
+    }
+
+    info!(
+        "{} Re-sending transaction {} of previous attempt",
+        id, signature
+    );
+    if signed_hash == hash {
+        if let Err(err) = client.send_transaction(&tx) {
+            warn!("{} Failed re-sending {}: {}", id, signature, err);
+        }
+    }
+    let signature = signature.to_string();
+    events::publish(id, Event::SignatureSent { signature });
+    Ok(Some(SentTransaction {
+        tx,
+        recency: Recency::Nonce {
+            account,
+            hash: signed_hash,
+        },
+    }))
+}
+
+/// Interval between checks of a transaction status.
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Number of status checks between re-sendings of a durable nonce transaction.
+const NONCE_RESEND_POLLS: usize = 10;
+
+/// Maximum time of waiting for a durable nonce transaction.
+const NONCE_CONFIRM_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Waits until the transaction reaches the `commitment`.
+/// A blockhash transaction fails when its blockhash expires.
+/// A durable nonce transaction is re-sent until the nonce is advanced or
+/// the timeout elapses; it's safe because the nonce can be used only once.
+/// The timed out transaction is kept to be re-sent by a retry of the request.
+fn confirm_transaction(
+    client: &RpcClient,
+    id: &ReqId,
+    sent: &SentTransaction,
+    commitment: CommitmentConfig,
+) -> Result<()> {
+    let signature = sent.signature();
+    debug!(
+        "{} Confirming transaction with commitment {:?}...",
+        id, commitment.commitment
+    );
+    let started = Instant::now();
+    let mut polls = 0;
+    loop {
+        match client.get_signature_status_with_commitment(&signature, commitment)? {
+            Some(Ok(())) => break,
+            Some(Err(err)) => {
+                nonce::forget(id);
+                return Err(err.into());
+            }
+            None => match sent.recency {
+                Recency::Blockhash(blockhash) => {
+                    if !client.is_blockhash_valid(&blockhash, CommitmentConfig::processed())? {
+                        return Err(eyre!(
+                            "Transaction {} is not confirmed: blockhash {} expired",
+                            signature,
+                            blockhash
+                        ));
+                    }
+                }
+                Recency::Nonce { account, hash } => {
+                    // Status may lag behind the nonce, so check it again before failing
+                    if nonce::blockhash(client, &account)? != hash
+                        && client.get_signature_status(&signature)?.is_none()
+                    {
+                        nonce::forget(id);
+                        return Err(eyre!(
+                            "Transaction {} is not confirmed: nonce {} advanced",
+                            signature,
+                            account
+                        ));
+                    }
+                    if started.elapsed() > NONCE_CONFIRM_TIMEOUT {
+                        return Err(eyre!(
+                            "Transaction {} is not confirmed in {:?}, retry the request with the same {}",
+                            signature,
+                            NONCE_CONFIRM_TIMEOUT,
+                            crate::id::HEADER
+                        ));
+                    }
+                    polls += 1;
+                    if polls % NONCE_RESEND_POLLS == 0 {
+                        debug!("{} Re-sending transaction {}...", id, signature);
+                        if let Err(err) = client.send_transaction(&sent.tx) {
+                            warn!("{} Failed re-sending {}: {}", id, signature, err);
+                        }
+                    }
+                }
+            },
+        }
+        std::thread::sleep(CONFIRM_POLL_INTERVAL);
+    }
+    debug!("{} Transaction is confirmed", id);
+    let signature = signature.to_string();
+    events::publish(id, Event::Confirmed { signature });
+
+    Ok(())
+}
+
+/// Represents failures of a deposit detected by simulation.
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum SimulationError {
+    #[error("Operator token account has insufficient funds: {0}")]
+    InsufficientFunds(String),
+
+    #[error("Account is not initialized: {0}")]
+    UninitializedAccount(String),
+
+    #[error("Account seed version mismatch: {0}")]
+    BadSeedVersion(String),
+
+    #[error("Transaction simulation failed: {0}")]
+    Failed(String),
+}
+
+/// Simulates the transaction and converts its failure into `SimulationError`.
+fn simulate_transaction(client: &RpcClient, id: &ReqId, tx: &Transaction) -> Result<()> {
+    debug!("{} Simulating transaction...", id);
+    let result = client.simulate_transaction(tx)?.value;
+    let logs = result.logs.unwrap_or_default();
+    info!(
+        "{} Simulation consumed {} compute units",
+        id,
+        result.units_consumed.unwrap_or_default()
+    );
+
+    match result.err {
+        None => {
+            for line in &logs {
+                debug!("{} Simulation log: {}", id, line);
+            }
+            Ok(())
+        }
+        Some(err) => {
+            for line in &logs {
+                error!("{} Simulation log: {}", id, line);
+            }
+            Err(classify_simulation_error(&err, &logs).into())
+        }
+    }
+}
+
+/// Maps simulation failure of common EVM loader and SPL token errors.
+fn classify_simulation_error(err: &TransactionError, logs: &[String]) -> SimulationError {
+    const INSUFFICIENT_FUNDS: &[&str] = &["insufficient funds", "insufficient lamports"];
+    const UNINITIALIZED: &[&str] = &[
+        "uninitialized",
+        "not initialized",
+        "invalid account data",
+        "accountnotinitialized",
+    ];
+    // The EVM loader checks addresses of ether accounts derived with the seed version
+    const BAD_SEED: &[&str] = &[
+        "expected pda address",
+        "provided seeds do not result in a valid address",
+    ];
+
+    let matches = |patterns: &[&str]| {
+        logs.iter()
+            .rev()
+            .find(|line| {
+                let line = line.to_lowercase();
+                patterns.iter().any(|p| line.contains(p))
+            })
+            .cloned()
+    };
+
+    if let TransactionError::InsufficientFundsForFee = err {
+        return SimulationError::InsufficientFunds(err.to_string());
+    }
+    if let Some(line) = matches(INSUFFICIENT_FUNDS) {
+        return SimulationError::InsufficientFunds(line);
+    }
+    if let Some(line) = matches(UNINITIALIZED) {
+        return SimulationError::UninitializedAccount(line);
+    }
+    if let Some(line) = matches(BAD_SEED) {
+        return SimulationError::BadSeedVersion(line);
+    }
+    SimulationError::Failed(err.to_string())
+}
+
+#[test]
+fn test_classify_simulation_error() {
+    use solana_sdk::instruction::InstructionError;
+
+    let err = TransactionError::InstructionError(1, InstructionError::Custom(1));
+    let logs = |lines: &[&str]| lines.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+
+    let e = classify_simulation_error(
+        &err,
+        &logs(&[
+            "Program TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA invoke [1]",
+            "Program log: Error: insufficient funds",
+        ]),
+    );
+    assert_eq!(
+        e,
+        SimulationError::InsufficientFunds("Program log: Error: insufficient funds".into())
+    );
+
+    let e = classify_simulation_error(&err, &logs(&["Program log: Error: UninitializedState"]));
+    assert!(matches!(e, SimulationError::UninitializedAccount(_)));
+
+    let e = classify_simulation_error(
+        &err,
+        &logs(&["Program log: Account 9xJ... - expected PDA address 7Yt..."]),
+    );
+    assert!(matches!(e, SimulationError::BadSeedVersion(_)));
+
+    let e = classify_simulation_error(&err, &logs(&["Program log: seed phrase of the game"]));
+    assert!(matches!(e, SimulationError::Failed(_)));
+
+    let e = classify_simulation_error(&err, &logs(&["Program log: something else"]));
+    assert!(matches!(e, SimulationError::Failed(_)));
+
+    let e = classify_simulation_error(&TransactionError::InsufficientFundsForFee, &[]);
+    assert!(matches!(e, SimulationError::InsufficientFunds(_)));
+}
+
 /// Maps an Ethereum address into a Solana address.
 fn ether_address_to_solana_pubkey(
     ether_address: &ethereum::Address,
     program_id: &Pubkey,
+    seed_version: u8,
 ) -> (Pubkey, u8) {
     Pubkey::find_program_address(
         &[
-            &[config::solana_account_seed_version()],
+            &[seed_version],
             ether_address.as_bytes(),
         ],
         program_id,
@@ -200,3 +829,93 @@ fn deposit_instruction(
         ],
     )
 }
+
+#[cfg(test)]
+fn test_accounts() -> DepositAccounts {
+    DepositAccounts {
+        signer: Pubkey::new_unique(),
+        signer_token: Pubkey::new_unique(),
+        evm_pool: Pubkey::new_unique(),
+        ether_account: Pubkey::new_unique(),
+        evm_loader: Pubkey::new_unique(),
+    }
+}
+
+#[test]
+fn test_deposit_instructions() {
+    let id = ReqId::default();
+    let accounts = test_accounts();
+    let ether_address = ethereum::Address::from_low_u64_be(0xDEADBEEF);
+
+    let ixs = deposit_instructions(&id, &accounts, ether_address, 10);
+    assert_eq!(ixs.len(), 3);
+    assert_eq!(ixs[1].program_id, spl_token::id());
+    assert_eq!(ixs[1].accounts[0].pubkey, accounts.signer_token);
+    assert_eq!(ixs[1].accounts[1].pubkey, accounts.ether_account);
+    assert_eq!(ixs[2].program_id, accounts.evm_loader);
+    assert_eq!(ixs[2].data[0], 0x27);
+    assert_eq!(&ixs[2].data[1..21], ether_address.as_bytes());
+}
+
+#[test]
+fn test_spl_transfer_instructions() {
+    let id = ReqId::default();
+    let signer = Pubkey::new_unique();
+    let wallet = Pubkey::new_unique();
+    let transfer = |decimals| SplTransfer {
+        mint: Pubkey::new_unique(),
+        amount: 10,
+        decimals,
+    };
+    let transfers = [transfer(6), transfer(9)];
+
+    let ixs = spl_transfer_instructions(&id, &signer, &wallet, &transfers, &[false, true]).unwrap();
+    assert_eq!(ixs.len(), 4);
+    assert_eq!(ixs[1].program_id, spl_token::id());
+    assert_eq!(ixs[2].program_id, spl_associated_token_account::id());
+    assert_eq!(ixs[3].program_id, spl_token::id());
+    assert_eq!(ixs[3].accounts[1].pubkey, transfers[1].mint);
+}
+
+#[test]
+fn test_send_deposit_with_mock_rpc() {
+    let id = ReqId::default();
+    let signer = Keypair::new();
+    let accounts = DepositAccounts {
+        signer: signer.pubkey(),
+        ..test_accounts()
+    };
+    let ether_address = ethereum::Address::from_low_u64_be(0xDEADBEEF);
+
+    let ixs = deposit_instructions(&id, &accounts, ether_address, 10);
+
+    let preflight = CommitmentConfig::processed();
+    let client = RpcClient::new_mock("succeeds".to_string());
+    assert!(send_transaction(&client, &id, &signer, &ixs, true, preflight, None).is_ok());
+    let sent = send_transaction(&client, &id, &signer, &ixs, false, preflight, None).unwrap();
+    assert!(matches!(sent.recency, Recency::Blockhash(_)));
+    let confirm = CommitmentConfig::finalized();
+    assert!(confirm_transaction(&client, &id, &sent, confirm).is_ok());
+
+    let client = RpcClient::new_mock("fails".to_string());
+    assert!(send_transaction(&client, &id, &signer, &ixs, false, preflight, None).is_err());
+}
+
+#[test]
+fn test_deposit_queue() {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    rt.block_on(async {
+        let token_account = Pubkey::new_unique();
+        let guard = lock_account(token_account).await;
+
+        let queue = DEPOSIT_QUEUES.lock().unwrap()[&token_account].clone();
+        assert!(queue.try_lock().is_err());
+        // Deposits from other token accounts are not blocked
+        drop(lock_account(Pubkey::new_unique()).await);
+
+        drop(guard);
+        assert!(queue.try_lock().is_ok());
+    });
+}
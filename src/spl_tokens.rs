@@ -0,0 +1,201 @@
+//! Faucet SPL tokens module.
+//!
+//! Sends SPL tokens (or wrapped NEON) from associated token accounts
+//! of the operator to a Solana wallet.
+
+use std::collections::HashMap;
+use std::str::FromStr as _;
+
+use eyre::{eyre, Result};
+use futures_locks::RwLock;
+use tracing::info;
+
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+
+use crate::{api, config, id::ReqId, solana};
+
+/// Represents packet of information needed for an SPL airdrop operation.
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct Airdrop {
+    /// Solana wallet of the recipient (base58).
+    pub wallet: String,
+    /// Mint address or symbol of the SPL token. If missing, do all tokens.
+    pub token: Option<String>,
+    /// Amount of each token to be received.
+    pub amount: u64,
+    /// Specifies amount in whole tokens (false, default) or in token fractions (true).
+    #[serde(default)]
+    pub in_fractions: bool,
+}
+
+impl api::Validate for Airdrop {
+    fn validate(&self) -> Result<(), String> {
+        Pubkey::from_str(&self.wallet)
+            .map_err(|e| format!("Invalid wallet '{}': {}", self.wallet, e))?;
+        if self.amount == 0 {
+            return Err("Amount must be positive".into());
+        }
+        Ok(())
+    }
+}
+
+/// Represents an error of a request which can't be served.
+#[derive(thiserror::Error, Debug)]
+#[error("{0}")]
+pub struct InvalidRequest(String);
+
+/// Represents a checked transfer of an SPL token with caps of the token.
+#[derive(Debug, Clone)]
+pub struct Transfer {
+    /// Mint as configured in `solana.spl_tokens`.
+    pub token: String,
+    /// Amount in token fractions.
+    pub amount: u64,
+    /// Largest amount in token fractions per request.
+    pub max_amount: u64,
+    /// Largest amount in token fractions per time slice.
+    pub per_time_max_amount: u64,
+    spl: solana::SplTransfer,
+}
+
+lazy_static::lazy_static! {
+    static ref DECIMALS: RwLock<HashMap<Pubkey, u8>> = RwLock::new(HashMap::new());
+}
+
+/// Resolves the recipient and requested tokens, converts amounts into fractions.
+pub async fn prepare(id: &ReqId, params: &Airdrop) -> Result<(Pubkey, Vec<Transfer>)> {
+    info!("{} Processing SPL {:?}...", id, params);
+
+    let wallet = Pubkey::from_str(&params.wallet)
+        .map_err(|e| InvalidRequest(format!("Invalid wallet '{}': {}", params.wallet, e)))?;
+
+    let tokens = match &params.token {
+        Some(token) => vec![find_token(token)?],
+        None => config::solana_spl_tokens(),
+    };
+    if tokens.is_empty() {
+        return Err(InvalidRequest("No SPL tokens are configured".into()).into());
+    }
+
+    let mut transfers = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        let mint = mint(&token).await?;
+        let decimals = decimals(mint).await?;
+        let to_fractions = |amount| whole_to_fractions(amount, decimals);
+        let amount = if params.in_fractions {
+            params.amount
+        } else {
+            to_fractions(params.amount)
+                .map_err(|e| InvalidRequest(format!("Invalid amount {}: {}", params.amount, e)))?
+        };
+        transfers.push(Transfer {
+            amount,
+            max_amount: to_fractions(config::solana_spl_token_max_amount(&token))?,
+            per_time_max_amount: to_fractions(config::solana_spl_token_per_time_max_amount(
+                &token,
+            ))?,
+            token,
+            spl: solana::SplTransfer {
+                mint,
+                amount,
+                decimals,
+            },
+        });
+    }
+
+    Ok((wallet, transfers))
+}
+
+/// Sends the tokens to the wallet in one transaction, returns its signature.
+pub async fn airdrop(id: &ReqId, wallet: Pubkey, transfers: &[Transfer]) -> Result<Signature> {
+    let operator = config::solana_operator_keypair()
+        .map_err(|e| eyre!("config::solana_operator_keypair: {:?}", e))?;
+    for t in transfers {
+        info!(
+            "{} Transfer {} of SPL token {} -> {}",
+            id, t.amount, t.token, wallet
+        );
+    }
+    let signature = solana::transfer_spl(
+        id,
+        operator,
+        wallet,
+        transfers.iter().map(|t| t.spl.clone()).collect(),
+    )
+    .await?;
+    info!("{} OK {}", id, signature);
+    Ok(signature)
+}
+
+/// Finds the configured token by mint or symbol.
+fn find_token(token: &str) -> Result<String> {
+    let tokens = config::solana_spl_tokens();
+    tokens
+        .iter()
+        .find(|mint| *mint == token)
+        .or_else(|| {
+            tokens.iter().find(|mint| {
+                let symbol = config::solana_spl_token_symbol(mint);
+                !symbol.is_empty() && symbol.eq_ignore_ascii_case(token)
+            })
+        })
+        .cloned()
+        .ok_or_else(|| {
+            InvalidRequest(format!(
+                "Unknown SPL token '{}'; valid options: {}",
+                token,
+                tokens.join(", ")
+            ))
+            .into()
+        })
+}
+
+/// Returns the mint of the configured token; resolves wrapped NEON of the default network.
+async fn mint(token: &str) -> Result<Pubkey> {
+    let mint = if token == config::SPL_NEON {
+        let network = config::DEFAULT_NETWORK;
+        if config::solana_account_seed_version(network) == 0 {
+            config::load_neon_params(network).await?;
+        }
+        config::solana_token_mint_id(network)
+    } else {
+        token.to_string()
+    };
+    Pubkey::from_str(&mint).map_err(|e| eyre!("Invalid mint '{}' of {}: {}", mint, token, e))
+}
+
+/// Returns decimals of the mint, reading them from the chain first time.
+async fn decimals(mint: Pubkey) -> Result<u8> {
+    if let Some(decimals) = DECIMALS.read().await.get(&mint) {
+        return Ok(*decimals);
+    }
+
+    let supply = tokio::task::spawn_blocking(move || {
+        let client =
+            RpcClient::new_with_commitment(config::solana_url(), config::solana_commitment());
+        client.get_token_supply(&mint)
+    })
+    .await??;
+    DECIMALS.write().await.insert(mint, supply.decimals);
+    Ok(supply.decimals)
+}
+
+/// Converts amount of whole tokens into fractions of a token with `decimals`.
+fn whole_to_fractions(amount: u64, decimals: u8) -> Result<u64> {
+    let factor = 10_u64
+        .checked_pow(decimals as u32)
+        .ok_or_else(|| eyre!("Overflow 10^{}", decimals))?;
+    amount
+        .checked_mul(factor)
+        .ok_or_else(|| eyre!("Overflow {}*{}", amount, factor))
+}
+
+#[test]
+fn test_whole_to_fractions() {
+    assert_eq!(whole_to_fractions(5, 0).unwrap(), 5);
+    assert_eq!(whole_to_fractions(5, 6).unwrap(), 5_000_000);
+    assert!(whole_to_fractions(u64::MAX, 1).is_err());
+    assert!(whole_to_fractions(1, 20).is_err());
+}
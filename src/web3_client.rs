@@ -0,0 +1,171 @@
+//! Faucet shared web3 client module.
+//!
+//! One HTTP client with a keep-alive connection pool is built at startup
+//! and shared by all requests along with contract handles parsed once per address.
+//! If `web3.ws_url` is set, new blocks are received over one WebSocket subscription
+//! shared by all waiters instead of polling.
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use eyre::{eyre, Result};
+use tokio::sync::{watch, Mutex};
+use tracing::{info, warn};
+
+use web3::contract::Contract;
+use web3::ethabi;
+use web3::futures::StreamExt as _;
+use web3::transports::{Http, WebSocket};
+use web3::types::Address;
+use web3::Web3;
+
+use crate::{config, id::ReqId};
+
+/// Interval of TCP keep-alive probes of connections to the Ethereum endpoint.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Longest wait for a new block from the subscription before checking anyway.
+const MAX_BLOCK_WAIT: Duration = Duration::from_secs(10);
+
+/// Receiver of numbers of new blocks from the shared WebSocket subscription.
+pub type NewHeads = watch::Receiver<u64>;
+
+/// Contract interfaces used by the faucet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Abi {
+    Erc20,
+    Erc20Mintable,
+    Disperse,
+    Erc721,
+    Erc1155,
+}
+
+impl Abi {
+    fn json(self) -> &'static [u8] {
+        match self {
+            Abi::Erc20 => include_bytes!("../erc20/ERC20.abi"),
+            Abi::Erc20Mintable => include_bytes!("../erc20/ERC20Mintable.abi"),
+            Abi::Disperse => include_bytes!("../erc20/Disperse.abi"),
+            Abi::Erc721 => include_bytes!("../nft/ERC721.abi"),
+            Abi::Erc1155 => include_bytes!("../nft/ERC1155.abi"),
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref CLIENT: RwLock<Option<Web3<Http>>> = RwLock::new(None);
+    static ref CONTRACTS: RwLock<HashMap<(Abi, Address), Contract<Http>>> =
+        RwLock::new(HashMap::new());
+    static ref HEADS: Mutex<Option<NewHeads>> = Mutex::new(None);
+}
+
+/// Builds the shared client from `web3.rpc_url` and `web3.request_timeout_secs`.
+pub fn init() -> Result<Web3<Http>> {
+    let timeout = config::web3_request_timeout_secs();
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(timeout))
+        .tcp_keepalive(KEEP_ALIVE_INTERVAL)
+        .build()
+        .map_err(|e| eyre!("Failed building web3 client: {}", e))?;
+    let url = config::web3_rpc_url();
+    let url = url
+        .parse()
+        .map_err(|e| eyre!("Invalid web3.rpc_url '{}': {}", url, e))?;
+    let web3 = Web3::new(Http::with_client(client, url));
+
+    *CLIENT.write().unwrap() = Some(web3.clone());
+    CONTRACTS.write().unwrap().clear();
+    info!("web3 client with request timeout {} s", timeout);
+    Ok(web3)
+}
+
+/// Returns the shared client, building it on first use.
+pub fn get() -> Result<Web3<Http>> {
+    if let Some(web3) = CLIENT.read().unwrap().as_ref() {
+        return Ok(web3.clone());
+    }
+    init()
+}
+
+/// Returns the handle of the contract at `address`; its interface is parsed only once.
+pub fn contract(abi: Abi, address: Address) -> Result<Contract<Http>> {
+    if let Some(contract) = CONTRACTS.read().unwrap().get(&(abi, address)) {
+        return Ok(contract.clone());
+    }
+
+    let interface = ethabi::Contract::load(abi.json())
+        .map_err(|e| eyre!("Failed reading {:?} ABI: {}", abi, e))?;
+    let contract = Contract::new(get()?.eth(), address, interface);
+    CONTRACTS
+        .write()
+        .unwrap()
+        .insert((abi, address), contract.clone());
+    Ok(contract)
+}
+
+/// Returns a receiver of new blocks from the subscription over `web3.ws_url`.
+/// The subscription is made once and shared; it's renewed after it breaks.
+/// Returns `None` if the WebSocket endpoint is not configured or unavailable.
+pub async fn new_heads(id: &ReqId) -> Option<NewHeads> {
+    let url = config::web3_ws_url();
+    if url.is_empty() {
+        return None;
+    }
+
+    let mut heads = HEADS.lock().await;
+    if let Some(receiver) = heads.as_ref() {
+        if receiver.has_changed().is_ok() {
+            return Some(receiver.clone());
+        }
+    }
+
+    let ws = match WebSocket::new(&url).await {
+        Ok(transport) => Web3::new(transport),
+        Err(e) => {
+            warn!("{} Failed connecting to {}: {}", id, url, e);
+            return None;
+        }
+    };
+    let mut stream = match ws.eth_subscribe().subscribe_new_heads().await {
+        Ok(stream) => stream,
+        Err(e) => {
+            warn!("{} Failed subscribing to new blocks: {}", id, e);
+            return None;
+        }
+    };
+
+    let (sender, receiver) = watch::channel(0);
+    tokio::spawn(async move {
+        // The connection lives as long as the subscription
+        let _ws = ws;
+        while let Some(Ok(header)) = stream.next().await {
+            let number = header.number.map_or(0, |n| n.as_u64());
+            if sender.send(number).is_err() {
+                break;
+            }
+        }
+        // Dropping the sender lets the waiters fall back to polling
+        warn!("Subscription to new blocks is closed");
+    });
+    *heads = Some(receiver.clone());
+    Some(receiver)
+}
+
+/// Waits for the next block from `heads` or sleeps `interval` without a subscription.
+/// Falls back to sleeping if the subscription breaks.
+pub async fn next_block(heads: &mut Option<NewHeads>, interval: Duration) {
+    let alive = match heads {
+        Some(receiver) => !matches!(
+            tokio::time::timeout(MAX_BLOCK_WAIT, receiver.changed()).await,
+            Ok(Err(_))
+        ),
+        None => {
+            tokio::time::sleep(interval).await;
+            return;
+        }
+    };
+    if !alive {
+        *heads = None;
+    }
+}